@@ -15,6 +15,8 @@ def create_arg_parser():
     parser = argparse.ArgumentParser()
     parser.add_argument('file')
     parser.add_argument('--save', action='store_true')
+    parser.add_argument('--lb', action='store_true', help='ratio w.r.t. the lower bound instead of opt')
+    parser.add_argument('--eta', choices=['simple', 'maxmin', 'inversion'], help='plot against this error measure (requires --errors when running the experiment)')
 
     return parser
 
@@ -23,18 +25,38 @@ def create_arg_parser():
 def legend(name, param):
     if "Im" in name:
         return f"MultiStage (ε = {param})"
+    elif "Gittins" in name or "SEPT" in name or "SETF" in name:
+        return name
+    elif "Malleable PTS" in name:
+        return f"Malleable PTS (λ = {param})"
+    elif "EQUI" in name:
+        return name
+    elif "Greedy PTS" in name:
+        return f"Greedy PTS (λ = {param})"
+    elif "Greedy" in name:
+        return name
+    elif "Adaptive" in name:
+        return f"Adaptive PTS (λ₀ = {param})"
+    elif "Learned" in name:
+        return "PRR (learned λ)"
     elif "PTS" in name or "PRR" in name:
         return f"PTS (λ = {param})"
     elif "Two" in name:
         return f"TwoStage (λ = {param})"
+    elif "restart" in name:
+        return f"NP-WSPT (restart, β = {param})"
+    elif "quantum" in name:
+        return f"Round-Robin (quantum {param})"
     elif "WDEQ" in name:
         return f"WDEQ"
     else:
         return "Round-Robin"
 
-def plot(filename, save):
-    if "exp1" in filename or "exp3" in filename:
+def plot(filename, save, lb=False, eta=None):
+    if "exp1" in filename or "exp3" in filename or "exp5" in filename:
         x_name = "sigma"
+    elif "exp4" in filename:
+        x_name = "inversion"
     else:
         x_name = "round"
 
@@ -42,13 +64,25 @@ def plot(filename, save):
 
     df = pd.read_csv(filename)
     df = df.round(3)
-    df['cr'] = df['alg'] / df['opt']
+    df['cr'] = df['alg'] / (df['lb'] if lb else df['opt'])
+    if eta is not None:
+        x_name = f"{eta}_error"
+    if x_name in ('inversion', f"{eta}_error"):
+        # error relative to the optimum, binned so that lines aggregate
+        df[x_name] = (df[x_name] / df['opt']).round(2)
     df['param'] = df[['name','param']].apply(lambda x: legend(*x),axis=1)
+    if 'spread' in df:
+        spread = df['spread'].notna()
+        df.loc[spread, 'param'] += df.loc[spread, 'spread'].map(lambda s: f" (spread {s})")
 
     ax = sns.lineplot(data=df, x=x_name, y="cr", hue='param', style='param', markers=('round' in list(df)), linewidth=2.5, markersize=8)
     handlers, _ = ax.get_legend_handles_labels()
 
-    if x_name == 'round':
+    if eta is not None:
+        ax.legend(handlers, df['param'].unique(),ncol=2, loc="upper left")
+        plt.xlabel(f"Prediction error η ({eta}) / OPT")
+        plt.ylabel('Empirical competitive ratio')
+    elif x_name == 'round':
         ax.xaxis.set_major_locator(MaxNLocator(integer=True))
         ax.legend(handlers,df['param'].unique(),ncol=2, loc="right", bbox_to_anchor=(1.0,0.63))
         plt.xlabel("Round")
@@ -64,7 +98,16 @@ def plot(filename, save):
             ax.set(xscale='symlog')
             plt.ylim(top=2.8)
             plt.xlabel("Noise parameter ω")
-            plt.ylabel('Empirical comp. ratio w.r.t. P-WSPT')
+            plt.ylabel('Empirical comp. ratio w.r.t. ' + ('lower bound' if lb else 'P-WSPT'))
+        if "exp5" in filename:
+            ax.legend(handlers, df['param'].unique(),ncol=2, loc="upper left")
+            ax.set(xscale='symlog')
+            plt.xlabel("Noise parameter ω")
+            plt.ylabel('Empirical competitive ratio')
+        if "exp4" in filename:
+            ax.legend(handlers, df['param'].unique(),ncol=2, loc="upper left")
+            plt.xlabel("Inversion error η / OPT")
+            plt.ylabel('Empirical competitive ratio')
 
     plt.tight_layout()
 
@@ -74,7 +117,8 @@ def plot(filename, save):
 
     if save:
         f = filename.split(".")[0]
-        plt.savefig(f"{f}.pdf")
+        suffix = ("-lb" if lb else "") + (f"-{eta}" if eta else "")
+        plt.savefig(f"{f}{suffix}.pdf")
     else:
         plt.show()
 
@@ -85,6 +129,6 @@ if __name__ == "__main__":
     parsed_args = arg_parser.parse_args(sys.argv[1:])
     if os.path.exists(parsed_args.file):
 
-        plot(parsed_args.file, parsed_args.save)
+        plot(parsed_args.file, parsed_args.save, parsed_args.lb, parsed_args.eta)
     else:
         print("Path not valid!")
@@ -1,132 +1,508 @@
-use crate::{instance::Instance, prediction::InstancePrediction};
+use std::collections::VecDeque;
+
+use crate::{
+    instance::ProblemInstance,
+    prediction::{InstancePrediction, Prediction},
+    schedule::Schedule,
+    scheduler::{Context, Scheduler},
+    simulator::{simulate, Policy, SimJob},
+};
+
+/// P-WSPT on the true lengths.
+pub fn pwspt(problem: &ProblemInstance, ctx: &Context) -> Schedule {
+    simulate(
+        problem,
+        &problem.instance,
+        &mut PWsptPolicy {
+            speeds: problem.machine_speeds(),
+        },
+        ctx,
+    )
+}
+
+pub fn wdeq(problem: &ProblemInstance, ctx: &Context) -> Schedule {
+    simulate(
+        problem,
+        &problem.instance,
+        &mut WdeqPolicy {
+            speeds: problem.machine_speeds(),
+        },
+        ctx,
+    )
+}
+
+pub fn pts(
+    problem: &ProblemInstance,
+    pred: &InstancePrediction,
+    robustification: f64,
+    ctx: &Context,
+) -> Schedule {
+    simulate(
+        problem,
+        pred,
+        &mut PtsPolicy {
+            speeds: problem.machine_speeds(),
+            robustification,
+            by_rank: false,
+        },
+        ctx,
+    )
+}
+
+/// PTS for a predicted order: the consistent part runs the first `m` alive jobs of the order,
+/// given by `ranks`, regardless of their weights.
+pub fn pts_by_rank(
+    problem: &ProblemInstance,
+    ranks: &InstancePrediction,
+    robustification: f64,
+    ctx: &Context,
+) -> Schedule {
+    simulate(
+        problem,
+        ranks,
+        &mut PtsPolicy {
+            speeds: problem.machine_speeds(),
+            robustification,
+            by_rank: true,
+        },
+        ctx,
+    )
+}
+
+/// PTS whose robustification grows with the prediction error revealed by completed jobs. It
+/// starts at `initial` and moves towards 1 by the weighted relative error
+/// `sum w_j |p_j - y_j| / sum w_j max(p_j, y_j)` of the completed jobs, so that it stays at
+/// `initial` for perfect predictions and approaches WDEQ for useless ones.
+pub fn adaptive_pts(
+    problem: &ProblemInstance,
+    pred: &InstancePrediction,
+    initial: f64,
+    ctx: &Context,
+) -> Schedule {
+    simulate(
+        problem,
+        pred,
+        &mut AdaptivePtsPolicy {
+            pts: PtsPolicy {
+                speeds: problem.machine_speeds(),
+                robustification: initial,
+                by_rank: false,
+            },
+            initial,
+            error: 0.0,
+            size: 0.0,
+        },
+        ctx,
+    )
+}
+
+/// SRPT on the true lengths: the `m` jobs of least remaining length run, the shortest on the
+/// fastest machine.
+pub fn srpt(problem: &ProblemInstance, ctx: &Context) -> Schedule {
+    simulate(
+        problem,
+        &problem.instance,
+        &mut SrptPolicy {
+            speeds: problem.machine_speeds(),
+        },
+        ctx,
+    )
+}
+
+/// FIFO: the `m` jobs released first run, ties broken by index.
+pub fn fifo(problem: &ProblemInstance, ctx: &Context) -> Schedule {
+    simulate(
+        problem,
+        &problem.instance,
+        &mut FifoPolicy {
+            speeds: problem.machine_speeds(),
+            releases: &problem.releases,
+        },
+        ctx,
+    )
+}
+
+/// Round robin with time slices instead of processor sharing: the `m` jobs at the head of a
+/// queue of the alive jobs run on whole machines until they have received `quantum` times
+/// their weight more processing, and then move to the end of the queue, which released jobs
+/// join as well. Every end of a slice preempts a job, which matters under switching costs.
+pub fn quantum_rr(problem: &ProblemInstance, quantum: f64, ctx: &Context) -> Schedule {
+    let n = problem.instance.len();
+    simulate(
+        problem,
+        &problem.instance,
+        &mut QuantumRrPolicy {
+            m: problem.m,
+            quantum,
+            queue: VecDeque::new(),
+            queued: vec![false; n],
+            slice_end: vec![f64::NAN; n],
+        },
+        ctx,
+    )
+}
+
+/// (W)SETF: processes the jobs of least attained processing (divided by the weight if
+/// `weighted`) on the `m` machines. Jobs at the same level share the machines left by the lower
+/// ones as in WDEQ, so that with unit weights they rise together.
+pub fn setf(problem: &ProblemInstance, weighted: bool, ctx: &Context) -> Schedule {
+    simulate(
+        problem,
+        &problem.instance,
+        &mut SetfPolicy {
+            m: problem.m,
+            weighted,
+        },
+        ctx,
+    )
+}
+
+/// The `i`-th job of `order` runs on the `i`-th machine, machines by non-increasing speed.
+fn speed_rates(order: Vec<usize>, speeds: &[f64], n: usize) -> Vec<f64> {
+    let mut rates = vec![0.0; n];
+    for (idx, &speed) in order.into_iter().zip(speeds) {
+        rates[idx] = speed;
+    }
+    rates
+}
 
+struct PWsptPolicy {
+    /// Machine speeds in non-increasing order.
+    speeds: Vec<f64>,
+}
+
+impl Policy for PWsptPolicy {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        speed_rates(pwspt_order(jobs), &self.speeds, jobs.len())
+    }
+}
+
+struct WdeqPolicy {
+    speeds: Vec<f64>,
+}
+
+impl Policy for WdeqPolicy {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        compute_related_wdeq_rates(jobs, &self.speeds)
+    }
+}
+
+struct PtsPolicy {
+    speeds: Vec<f64>,
+    robustification: f64,
+    /// Predictions are ranks in a predicted order rather than lengths.
+    by_rank: bool,
+}
+
+impl Policy for PtsPolicy {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        let mut rates: Vec<f64> = compute_related_wdeq_rates(jobs, &self.speeds)
+            .into_iter()
+            .map(|rate| self.robustification * rate)
+            .collect();
+        let order = if self.by_rank {
+            rank_order(jobs)
+        } else {
+            pwspt_order(jobs)
+        };
+        for (idx, &speed) in order.into_iter().zip(&self.speeds) {
+            rates[idx] += (1.0 - self.robustification) * speed;
+        }
+        rates
+    }
+}
 
-type ID = usize;
+struct AdaptivePtsPolicy {
+    pts: PtsPolicy,
+    initial: f64,
+    /// Weighted absolute prediction error of the completed jobs.
+    error: f64,
+    /// Weighted maximum of length and prediction of the completed jobs.
+    size: f64,
+}
+
+impl Policy for AdaptivePtsPolicy {
+    fn rates(&mut self, time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        self.pts.rates(time, jobs)
+    }
 
-#[derive(Clone, Copy, Debug)]
-struct Job {
-    id: ID,
-    weight: f64,
-    pred: f64,
-    length: f64,
+    fn completed(&mut self, _time: f64, job: &SimJob) {
+        self.error += job.weight * (job.attained - job.pred).abs();
+        self.size += job.weight * job.attained.max(job.pred);
+        self.pts.robustification = self.initial + (1.0 - self.initial) * self.error / self.size;
+    }
+}
+
+struct SrptPolicy {
+    speeds: Vec<f64>,
 }
 
-impl PartialEq for Job {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+impl Policy for SrptPolicy {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        let mut order: Vec<usize> = (0..jobs.len()).collect();
+        order.sort_by(|&i, &j| {
+            jobs[i]
+                .remaining
+                .partial_cmp(&jobs[j].remaining)
+                .unwrap()
+                .then(jobs[i].id.cmp(&jobs[j].id))
+        });
+        speed_rates(order, &self.speeds, jobs.len())
     }
 }
 
-impl Eq for Job {}
+struct FifoPolicy<'a> {
+    speeds: Vec<f64>,
+    releases: &'a [f64],
+}
 
-pub fn pwspt(
-    instance: &Instance,
-    weights: &[f64],
-    releases: &[usize],
+impl Policy for FifoPolicy<'_> {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        let mut order: Vec<usize> = (0..jobs.len()).collect();
+        order.sort_by(|&i, &j| {
+            self.releases[jobs[i].id]
+                .partial_cmp(&self.releases[jobs[j].id])
+                .unwrap()
+                .then(jobs[i].id.cmp(&jobs[j].id))
+        });
+        speed_rates(order, &self.speeds, jobs.len())
+    }
+}
+
+/// Relative tolerance below which the rest of a slice counts as zero.
+const SLICE_EPS: f64 = 1e-9;
+
+struct QuantumRrPolicy {
     m: usize,
-    scale: usize,
-) -> f64 {
-    let mut t: usize = 0;
-    let mut obj: usize = 0;
-
-    let mut n = releases.len();
-    let mut jobs: Vec<Job> = vec![];
-
-    loop {
-        // interval [t,t+1]
-        let released_jobs: Vec<ID> = releases.iter().enumerate().filter(|(_, &r)| r * scale == t).map(|(id, _)| id).collect();
-        if !released_jobs.is_empty() {
-            for j in released_jobs {
-                jobs.push(Job { id: j, weight: weights[j], pred: instance[j] * scale as f64, length: instance[j] * scale as f64});
+    quantum: f64,
+    /// Alive job ids in round robin order.
+    queue: VecDeque<usize>,
+    /// Whether a job has joined the queue, by job id.
+    queued: Vec<bool>,
+    /// Attained processing at which the slice of a running job ends, by job id; `NaN` for
+    /// jobs that wait.
+    slice_end: Vec<f64>,
+}
+
+impl Policy for QuantumRrPolicy {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        let mut position = vec![None; self.queued.len()];
+        for (idx, job) in jobs.iter().enumerate() {
+            position[job.id] = Some(idx);
+            if !self.queued[job.id] {
+                self.queued[job.id] = true;
+                self.queue.push_back(job.id);
             }
-            jobs.sort_by(|j1,j2| (j2.weight / j2.pred).partial_cmp(&(j1.weight / j1.pred)).unwrap());
         }
-
-        // P-WSPT
-        let pwspt: Vec<&mut Job> = jobs.iter_mut().take(m).collect();
-        for j in pwspt {
-            j.length -= 1.0
+        self.queue.retain(|&id| position[id].is_some());
+
+        if self.queue.len() > self.m {
+            let slice_end = &self.slice_end;
+            let expired: Vec<usize> = self
+                .queue
+                .iter()
+                .take(self.m)
+                .cloned()
+                .filter(|&id| {
+                    let attained = jobs[position[id].unwrap()].attained;
+                    attained >= slice_end[id] - SLICE_EPS * slice_end[id].max(1.0)
+                })
+                .collect();
+            self.queue.retain(|id| !expired.contains(id));
+            for id in expired {
+                self.slice_end[id] = f64::NAN;
+                self.queue.push_back(id);
+            }
         }
 
-        t += 1;
-        
-        // clear finished jobs
-        let n_finished = jobs.iter().filter(|j| j.length <= 0.0).count();
-        n -= n_finished;
-        obj += t * n_finished;
-        jobs.retain(|j| j.length > 0.0);
+        let mut rates = vec![0.0; jobs.len()];
+        for &id in self.queue.iter().take(self.m) {
+            let job = &jobs[position[id].unwrap()];
+            if self.slice_end[id].is_nan() {
+                self.slice_end[id] = job.attained + self.quantum * job.weight;
+            }
+            rates[position[id].unwrap()] = 1.0;
+        }
+        rates
+    }
 
-        if n == 0 {
-            return obj as f64 / scale as f64
+    fn horizon(&self, _time: f64, jobs: &[SimJob], rates: &[f64]) -> Option<f64> {
+        if self.queue.len() <= self.m {
+            return None;
         }
+        jobs.iter()
+            .zip(rates)
+            .filter(|(_, &rate)| rate > 0.0)
+            .map(|(job, _)| self.slice_end[job.id] - job.attained)
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
     }
 }
 
-pub fn wdeq(
-    instance: &Instance,
-    weights: &[f64],
-    releases: &[usize],
+struct SetfPolicy {
     m: usize,
-    scale: usize,
-) -> f64 {
-    let mut t: usize = 0;
-    let mut obj: usize = 0;
-
-    let mut n = releases.len();
-    let mut jobs: Vec<Job> = vec![];
-
-    let mut wdeq_rates: Vec<f64> = vec![];
-    let mut recompute_rates = true;
-
-    loop {
-        // interval [t,t+1]
-        let released_jobs: Vec<ID> = releases.iter().enumerate().filter(|(_, &r)| r * scale == t).map(|(id, _)| id).collect();
-        if !released_jobs.is_empty() {
-            for j in released_jobs {
-                jobs.push(Job { id: j, weight: weights[j], pred: instance[j] * scale as f64, length: instance[j] * scale as f64});
-            }
-            recompute_rates = true;
-        }
+    weighted: bool,
+}
 
-        if recompute_rates {
-            wdeq_rates = compute_wdeq_rates(&jobs, m);
-            recompute_rates = false;
+impl SetfPolicy {
+    fn weight(&self, job: &SimJob) -> f64 {
+        if self.weighted {
+            job.weight
+        } else {
+            1.0
         }
-        for (idx, j) in jobs.iter_mut().enumerate() {
-            j.length -= wdeq_rates[idx];
+    }
+
+    fn level(&self, job: &SimJob) -> f64 {
+        job.attained / self.weight(job)
+    }
+
+    /// Indices of `jobs` by level, split into groups of (numerically) equal level.
+    fn groups(&self, jobs: &[SimJob]) -> Vec<Vec<usize>> {
+        let mut order: Vec<usize> = (0..jobs.len()).collect();
+        order.sort_by(|&i, &j| {
+            self.level(&jobs[i])
+                .partial_cmp(&self.level(&jobs[j]))
+                .unwrap()
+                .then(jobs[i].id.cmp(&jobs[j].id))
+        });
+        let mut groups: Vec<Vec<usize>> = vec![];
+        for idx in order {
+            let level = self.level(&jobs[idx]);
+            match groups.last_mut() {
+                Some(group) if level - self.level(&jobs[group[0]]) <= SETF_EPS * level.max(1.0) => {
+                    group.push(idx)
+                }
+                _ => groups.push(vec![idx]),
+            }
         }
+        groups
+    }
+}
 
-        t += 1;
-        
-        // clear finished jobs
-        let n_finished = jobs.iter().filter(|j| j.length <= 0.0).count();
-        if n_finished > 0 {
-            recompute_rates = true;
+/// Relative difference below which two jobs are at the same level in SETF.
+const SETF_EPS: f64 = 1e-9;
+
+impl Policy for SetfPolicy {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        let mut rates = vec![0.0; jobs.len()];
+        let mut free = self.m;
+        for group in self.groups(jobs) {
+            if free == 0 {
+                break;
+            }
+            let weights: Vec<f64> = group.iter().map(|&idx| self.weight(&jobs[idx])).collect();
+            for (&idx, rate) in group.iter().zip(wdeq_shares(&weights, free)) {
+                rates[idx] = rate;
+            }
+            free = free.saturating_sub(group.len());
         }
-        n -= n_finished;
-        obj += t * n_finished;
-        jobs.retain(|j| j.length > 0.0);
+        rates
+    }
 
-        if n == 0 {
-            return obj as f64 / scale as f64
+    /// The first time a job catches up with the next higher group; within a group, weighted
+    /// jobs capped at one machine fall behind the others.
+    fn horizon(&self, _time: f64, jobs: &[SimJob], rates: &[f64]) -> Option<f64> {
+        let speed = |idx: &usize| rates[*idx] / self.weight(&jobs[*idx]);
+        self.groups(jobs)
+            .windows(2)
+            .filter_map(|pair| {
+                let fastest = pair[0].iter().map(speed).fold(0.0, f64::max);
+                let slowest = pair[1].iter().map(speed).fold(f64::INFINITY, f64::min);
+                let gap = self.level(&jobs[pair[1][0]]) - self.level(&jobs[pair[0][0]]);
+                if fastest > slowest {
+                    Some(gap / (fastest - slowest))
+                } else {
+                    None
+                }
+            })
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+}
+
+/// Indices of `jobs` by non-increasing ratio of weight and predicted length.
+pub fn pwspt_order(jobs: &[SimJob]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..jobs.len()).collect();
+    order.sort_by(|&i, &j| {
+        (jobs[j].weight / jobs[j].pred)
+            .partial_cmp(&(jobs[i].weight / jobs[i].pred))
+            .unwrap()
+            .then(jobs[i].id.cmp(&jobs[j].id))
+    });
+    order
+}
+
+/// Indices of `jobs` by increasing predicted rank.
+fn rank_order(jobs: &[SimJob]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..jobs.len()).collect();
+    order.sort_by(|&i, &j| jobs[i].pred.partial_cmp(&jobs[j].pred).unwrap());
+    order
+}
+
+/// WDEQ: machines shared in proportion to the weights, at most one machine per job.
+pub fn compute_wdeq_rates(jobs: &[SimJob], m: usize) -> Vec<f64> {
+    let weights: Vec<f64> = jobs.iter().map(|job| job.weight).collect();
+    wdeq_shares(&weights, m)
+}
+
+/// WDEQ on machines with the given speeds in non-increasing order.
+pub fn compute_related_wdeq_rates(jobs: &[SimJob], speeds: &[f64]) -> Vec<f64> {
+    let weights: Vec<f64> = jobs.iter().map(|job| job.weight).collect();
+    if speeds.iter().all(|&s| s == 1.0) {
+        wdeq_shares(&weights, speeds.len())
+    } else {
+        related_wdeq_shares(&weights, speeds)
+    }
+}
+
+/// Rates of WDEQ on uniformly related machines with speeds in non-increasing order. Rates are
+/// proportional to the weights, except that no `k` jobs may get more than the `k` fastest
+/// machines together. If the `k` heaviest jobs would (for the smallest such `k`), they share
+/// these machines in proportion to their weights, and the other jobs share the other machines
+/// in the same way. With unit speeds this is [`wdeq_shares`].
+pub fn related_wdeq_shares(weights: &[f64], speeds: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..weights.len()).collect();
+    order.sort_by(|&i, &j| weights[j].partial_cmp(&weights[i]).unwrap().then(i.cmp(&j)));
+    let mut rates = vec![0.0; weights.len()];
+    let (mut jobs, mut machines) = (&order[..], speeds);
+    while !jobs.is_empty() && !machines.is_empty() {
+        let weight_sum = total_weight(weights, jobs);
+        let speed_sum: f64 = machines.iter().sum();
+        let (mut weight, mut speed) = (0.0, 0.0);
+        let mut group = (jobs, speed_sum);
+        for i in 0..jobs.len().min(machines.len()) {
+            weight += weights[jobs[i]];
+            speed += machines[i];
+            if weight * speed_sum > speed * weight_sum {
+                group = (&jobs[..=i], speed);
+                break;
+            }
+        }
+        let (group, group_speed) = group;
+        let group_weight = total_weight(weights, group);
+        for &j in group {
+            rates[j] = weights[j] * group_speed / group_weight;
         }
+        jobs = &jobs[group.len()..];
+        machines = &machines[group.len().min(machines.len())..];
     }
+    rates
 }
 
-fn compute_wdeq_rates(jobs: &[Job], m: usize) -> Vec<f64> {
+/// Rates of WDEQ for jobs with the given weights on `m` machines.
+pub fn wdeq_shares(weights: &[f64], m: usize) -> Vec<f64> {
     let mut rm = m;
-    let mut rem_jobs: Vec<usize> = (0..jobs.len()).collect();
-    let n = jobs.len();
-    let mut rates: Vec<f64> = vec![0.0;n];
+    let mut rem_jobs: Vec<usize> = (0..weights.len()).collect();
+    let n = weights.len();
+    let mut rates: Vec<f64> = vec![0.0; n];
     'find: loop {
-        let wk = total_weight(&jobs, &rem_jobs);
+        let wk = total_weight(weights, &rem_jobs);
         let mut job: Option<usize> = None;
         for &j in &rem_jobs {
-            if jobs[j].weight * rm as f64 / wk >= 1.0 {
+            if weights[j] * rm as f64 / wk >= 1.0 {
                 rates[j] = 1.0;
                 rm -= 1;
-                job = Some(j.clone());
-                break
+                job = Some(j);
+                break;
             }
         }
         if let Some(job) = job {
@@ -135,78 +511,314 @@ fn compute_wdeq_rates(jobs: &[Job], m: usize) -> Vec<f64> {
             break 'find;
         }
     }
-    let wk = total_weight(&jobs, &rem_jobs);
+    let wk = total_weight(weights, &rem_jobs);
     for j in rem_jobs {
-        rates[j] = jobs[j].weight * rm as f64 / wk;
+        rates[j] = weights[j] * rm as f64 / wk;
     }
     rates
 }
 
-pub fn pts(
-    instance: &Instance,
-    pred: &InstancePrediction,
-    weights: &[f64],
-    releases: &[usize],
-    robustification: f64,
-    m : usize,
-    scale: usize,
-) -> f64 {
-    let mut t: usize = 0;
-    let mut obj: usize = 0;
-
-    let mut n = releases.len();
-    let mut jobs: Vec<Job> = vec![];
-
-    let mut wdeq_rates: Vec<f64> = vec![];
-    let mut recompute_rates = true;
-
-    loop {
-        // interval [t,t+1]
-        let released_jobs: Vec<ID> = releases.iter().enumerate().filter(|(_, &r)| r * scale == t).map(|(id, _)| id).collect();
-        if !released_jobs.is_empty() {
-            for j in released_jobs {
-                jobs.push(Job { id: j, weight: weights[j], pred: pred[j] * scale as f64, length: instance[j] * scale as f64});
-            }
-            jobs.sort_by(|j1,j2| (j2.weight / j2.pred).partial_cmp(&(j1.weight / j1.pred)).unwrap());
-            recompute_rates = true;
-        }
+fn total_weight(weights: &[f64], indices: &[usize]) -> f64 {
+    indices.iter().map(|&i| weights[i]).sum()
+}
 
-        // P-WSPT
-        let pwspt: Vec<&mut Job> = jobs.iter_mut().take(m).collect();
-        for j in pwspt {
-            j.length -= 1.0 - robustification
-        }
+pub struct PWspt;
+
+impl Scheduler for PWspt {
+    fn name(&self) -> &str {
+        "P-WSPT"
+    }
 
-        // WDEQ
-        if recompute_rates {
-            wdeq_rates = compute_wdeq_rates(&jobs, m);
-            recompute_rates = false;
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        pwspt(problem, ctx)
+    }
+}
+
+pub struct Wdeq;
+
+impl Scheduler for Wdeq {
+    fn name(&self) -> &str {
+        "WDEQ"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        wdeq(problem, ctx)
+    }
+}
+
+pub struct Srpt;
+
+impl Scheduler for Srpt {
+    fn name(&self) -> &str {
+        "SRPT"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        srpt(problem, ctx)
+    }
+}
+
+pub struct Fifo;
+
+impl Scheduler for Fifo {
+    fn name(&self) -> &str {
+        "FIFO"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        fifo(problem, ctx)
+    }
+}
+
+pub struct QuantumRoundRobin {
+    pub quantum: f64,
+}
+
+impl Scheduler for QuantumRoundRobin {
+    fn name(&self) -> &str {
+        "Round-Robin (quantum)"
+    }
+
+    fn param(&self) -> f64 {
+        self.quantum
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        quantum_rr(problem, self.quantum, ctx)
+    }
+}
+
+pub struct Setf {
+    pub weighted: bool,
+}
+
+impl Scheduler for Setf {
+    fn name(&self) -> &str {
+        if self.weighted {
+            "WSETF"
+        } else {
+            "SETF"
         }
-        for (idx, j) in jobs.iter_mut().enumerate() {
-            j.length -= robustification * wdeq_rates[idx];
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    /// Under switching costs, a heavy job capped at one machine progresses at its full rate
+    /// while the jobs sharing the machines at its level progress slower than their rates, so
+    /// no rates keep a level of WSETF together and it splits and merges ever faster.
+    fn supports_switch_cost(&self) -> bool {
+        !self.weighted
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        setf(problem, self.weighted, ctx)
+    }
+}
+
+pub struct Pts {
+    pub robustification: f64,
+}
+
+impl Scheduler for Pts {
+    fn name(&self) -> &str {
+        "PTS"
+    }
+
+    fn param(&self) -> f64 {
+        self.robustification
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        pts(problem, pred.lengths(), self.robustification, ctx)
+    }
+}
+
+pub struct AdaptivePts {
+    pub initial: f64,
+}
+
+impl Scheduler for AdaptivePts {
+    fn name(&self) -> &str {
+        "Adaptive PTS"
+    }
+
+    fn param(&self) -> f64 {
+        self.initial
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        adaptive_pts(problem, pred.lengths(), self.initial, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    fn context() -> Context {
+        Context {
+            record_profile: true,
+            ..Context::default()
         }
+    }
 
-        t += 1;
-        
-        // clear finished jobs
-        let n_finished = jobs.iter().filter(|j| j.length <= 0.0).count();
-        if n_finished > 0 {
-            recompute_rates = true;
+    /// Two jobs of length 4 on one machine, the second released at 2.
+    fn staggered(weights: Vec<f64>) -> ProblemInstance {
+        ProblemInstance {
+            weights,
+            releases: vec![0.0, 2.0],
+            ..ProblemInstance::single_machine(vec![4.0, 4.0].into())
         }
-        n -= n_finished;
-        obj += t * n_finished;
-        jobs.retain(|j| j.length > 0.0);
+    }
 
-        if n == 0 {
-            return obj as f64 / scale as f64
+    #[test]
+    fn setf_merges_levels_at_the_horizon() {
+        // the late job runs alone until it has caught up at time 4, then both share the machine
+        let schedule = setf(&staggered(vec![1.0, 1.0]), false, &context());
+        let merged = schedule
+            .profile
+            .iter()
+            .find(|segment| segment.rates.len() == 2)
+            .unwrap();
+        assert_close(merged.start, 4.0);
+        for &(_, rate) in &merged.rates {
+            assert_close(rate, 0.5);
         }
+        assert_close(schedule.completions[0], 8.0);
+        assert_close(schedule.completions[1], 8.0);
     }
-}
 
-fn total_weight(jobs: &[Job], indices: &[usize]) -> f64 {
-    indices.iter().map(|&i| jobs[i].weight).sum()
-}
+    #[test]
+    fn wsetf_shares_a_level_by_weight() {
+        // processing over weight rises equally at rates 2/3 and 1/3 until the heavy job completes
+        let problem = ProblemInstance {
+            releases: vec![0.0, 0.0],
+            ..staggered(vec![2.0, 1.0])
+        };
+        let schedule = setf(&problem, true, &context());
+        assert_close(schedule.completions[0], 6.0);
+        assert_close(schedule.completions[1], 8.0);
+    }
 
+    #[test]
+    fn related_wdeq_caps_heavy_jobs_at_the_fastest_machines() {
+        // 10/11 of the total speed 3.9 would exceed the fastest machine
+        let rates = related_wdeq_shares(&[10.0, 1.0], &[2.0, 1.9]);
+        assert_close(rates[0], 2.0);
+        assert_close(rates[1], 1.9);
 
+        // equal weights share the total speed, which no job's share exceeds
+        for rate in related_wdeq_shares(&[1.0, 1.0, 1.0], &[2.0, 1.0]) {
+            assert_close(rate, 1.0);
+        }
 
+        // with unit speeds this is WDEQ
+        let weights = [5.0, 1.0, 1.0, 3.0];
+        for (a, b) in related_wdeq_shares(&weights, &[1.0; 3])
+            .into_iter()
+            .zip(wdeq_shares(&weights, 3))
+        {
+            assert_close(a, b);
+        }
+    }
 
+    #[test]
+    fn adaptive_pts_robustifies_on_bad_predictions() {
+        let mut policy = AdaptivePtsPolicy {
+            pts: PtsPolicy {
+                speeds: vec![1.0],
+                robustification: 0.2,
+                by_rank: false,
+            },
+            initial: 0.2,
+            error: 0.0,
+            size: 0.0,
+        };
+        let mut complete = |attained: f64, pred: f64| {
+            let job = SimJob {
+                id: 0,
+                weight: 1.0,
+                pred,
+                remaining: 0.0,
+                attained,
+            };
+            policy.completed(attained, &job);
+            policy.pts.robustification
+        };
+        assert_close(complete(2.0, 2.0), 0.2);
+        // error 3 over size 6
+        assert_close(complete(4.0, 1.0), 0.6);
+        // error 3 + 7 over size 6 + 8
+        assert_close(complete(1.0, 8.0), 0.2 + 0.8 * 10.0 / 14.0);
+    }
+}
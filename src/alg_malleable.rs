@@ -0,0 +1,267 @@
+//! Algorithms for malleable jobs that can run on several of the `m` identical machines at once
+//! according to their speed-up curves. They allocate fractional numbers of machines; the rate
+//! of a job is its speed-up curve at its allocation. Sequential jobs are linear up to one
+//! machine, so that all algorithms also run on ordinary instances.
+
+use crate::{
+    instance::ProblemInstance,
+    prediction::{InstancePrediction, Prediction},
+    schedule::Schedule,
+    scheduler::{Context, Scheduler},
+    simulator::{simulate, Policy, SimJob},
+};
+
+/// EQUI with weights: every alive job gets `m w_j / W` machines for the total weight `W`,
+/// without looking at lengths or speed-up curves. With unit weights this is EQUI (Edmonds,
+/// 1999); unlike WDEQ, it does not cap the shares at one machine.
+pub fn equi(problem: &ProblemInstance, ctx: &Context) -> Schedule {
+    simulate(
+        problem,
+        &problem.instance,
+        &mut MalleablePolicy {
+            problem,
+            robustification: 1.0,
+            fifo: false,
+        },
+        ctx,
+    )
+}
+
+/// PTS for malleable jobs: the allocation is `λ` times the one of [`equi`] plus `1 - λ` times
+/// the greedy allocation on the predictions, which hands out the machines one at a time to the
+/// job whose predicted weighted progress `w_j / y_j` times the rate it gains from the machine
+/// is largest. For sequential jobs the greedy allocation is predicted P-WSPT.
+pub fn malleable_pts(
+    problem: &ProblemInstance,
+    pred: &InstancePrediction,
+    robustification: f64,
+    ctx: &Context,
+) -> Schedule {
+    simulate(
+        problem,
+        pred,
+        &mut MalleablePolicy {
+            problem,
+            robustification,
+            fifo: false,
+        },
+        ctx,
+    )
+}
+
+/// Clairvoyant FIFO for malleable jobs: the jobs take whole machines in the order of their
+/// release dates, one each, and spare machines go to the jobs that gain most from them.
+pub fn malleable_fifo(problem: &ProblemInstance, ctx: &Context) -> Schedule {
+    simulate(
+        problem,
+        &problem.instance,
+        &mut MalleablePolicy {
+            problem,
+            robustification: 0.0,
+            fifo: true,
+        },
+        ctx,
+    )
+}
+
+struct MalleablePolicy<'a> {
+    problem: &'a ProblemInstance,
+    robustification: f64,
+    /// Hand out the machines in the order of the release dates instead of greedily.
+    fifo: bool,
+}
+
+impl MalleablePolicy<'_> {
+    /// Whole machines for `jobs`, handed out greedily by marginal predicted weighted rate, which
+    /// maximises the predicted weighted rate among allocations of whole machines since the
+    /// speed-up curves are concave. Machines that no job gains from stay idle.
+    fn greedy_machines(&self, jobs: &[SimJob]) -> Vec<f64> {
+        let mut machines = vec![0.0; jobs.len()];
+        for _ in 0..self.problem.m {
+            let (mut best, mut best_gain) = (None, 0.0);
+            for (idx, job) in jobs.iter().enumerate() {
+                let curve = self.problem.speedup(job.id);
+                let gain = job.weight / job.pred
+                    * (curve.rate(machines[idx] + 1.0) - curve.rate(machines[idx]));
+                if gain > best_gain {
+                    best = Some(idx);
+                    best_gain = gain;
+                }
+            }
+            match best {
+                Some(idx) => machines[idx] += 1.0,
+                None => break,
+            }
+        }
+        machines
+    }
+
+    /// Whole machines for `jobs` in the order of their release dates: the first `m` jobs get
+    /// one machine each, and machines left over go one at a time to the job whose rate they
+    /// increase most, the earliest released one on ties. For sequential jobs this is FIFO.
+    fn fifo_machines(&self, jobs: &[SimJob]) -> Vec<f64> {
+        let mut order: Vec<usize> = (0..jobs.len()).collect();
+        order.sort_by(|&a, &b| {
+            self.problem.releases[jobs[a].id]
+                .partial_cmp(&self.problem.releases[jobs[b].id])
+                .unwrap()
+                .then(jobs[a].id.cmp(&jobs[b].id))
+        });
+        let mut machines = vec![0.0; jobs.len()];
+        for &idx in order.iter().take(self.problem.m) {
+            machines[idx] = 1.0;
+        }
+        for _ in jobs.len()..self.problem.m {
+            let (mut best, mut best_gain) = (None, 0.0);
+            for &idx in &order {
+                let curve = self.problem.speedup(jobs[idx].id);
+                let gain = curve.rate(machines[idx] + 1.0) - curve.rate(machines[idx]);
+                if gain > best_gain {
+                    best = Some(idx);
+                    best_gain = gain;
+                }
+            }
+            match best {
+                Some(idx) => machines[idx] += 1.0,
+                None => break,
+            }
+        }
+        machines
+    }
+}
+
+impl Policy for MalleablePolicy<'_> {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        let weight: f64 = jobs.iter().map(|job| job.weight).sum();
+        let mut machines: Vec<f64> = jobs
+            .iter()
+            .map(|job| self.robustification * self.problem.m as f64 * job.weight / weight)
+            .collect();
+        if self.robustification < 1.0 {
+            let greedy = if self.fifo {
+                self.fifo_machines(jobs)
+            } else {
+                self.greedy_machines(jobs)
+            };
+            for (share, greedy) in machines.iter_mut().zip(greedy) {
+                *share += (1.0 - self.robustification) * greedy;
+            }
+        }
+        jobs.iter()
+            .zip(machines)
+            .map(|(job, x)| self.problem.speedup(job.id).rate(x))
+            .collect()
+    }
+}
+
+pub struct Equi;
+
+impl Scheduler for Equi {
+    fn name(&self) -> &str {
+        "EQUI"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        equi(problem, ctx)
+    }
+}
+
+pub struct MalleablePts {
+    pub robustification: f64,
+}
+
+impl Scheduler for MalleablePts {
+    fn name(&self) -> &str {
+        "Malleable PTS"
+    }
+
+    fn param(&self) -> f64 {
+        self.robustification
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        malleable_pts(problem, pred.lengths(), self.robustification, ctx)
+    }
+}
+
+pub struct MalleableFifo;
+
+impl Scheduler for MalleableFifo {
+    fn name(&self) -> &str {
+        "Malleable FIFO"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        malleable_fifo(problem, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::SpeedUp;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    /// Jobs of lengths 4 and 8 on 4 machines, released at 0 with the given speed-up bounds.
+    fn problem(bounds: [f64; 2]) -> ProblemInstance {
+        ProblemInstance {
+            m: 4,
+            speedups: bounds
+                .iter()
+                .map(|&bound| SpeedUp::Linear { bound })
+                .collect(),
+            ..ProblemInstance::single_machine(vec![4.0, 8.0].into())
+        }
+    }
+
+    #[test]
+    fn equi_shares_the_machines_by_weight() {
+        // two machines each, until the short job completes and the long one gets all four
+        let schedule = equi(&problem([4.0, 4.0]), &Context::default());
+        assert_close(schedule.completions[0], 2.0);
+        assert_close(schedule.completions[1], 3.0);
+
+        // three machines and one, and the long job runs at rate 4 once the short one is done
+        let weighted = ProblemInstance {
+            weights: vec![3.0, 1.0],
+            ..problem([4.0, 4.0])
+        };
+        let schedule = equi(&weighted, &Context::default());
+        assert_close(schedule.completions[0], 4.0 / 3.0);
+        assert_close(schedule.completions[1], 3.0);
+    }
+
+    #[test]
+    fn malleable_pts_hands_out_machines_by_gain() {
+        // with exact predictions, the short job takes all machines it can use
+        let parallel = problem([4.0, 4.0]);
+        let schedule = malleable_pts(&parallel, &parallel.instance, 0.0, &Context::default());
+        assert_close(schedule.completions[0], 1.0);
+        assert_close(schedule.completions[1], 3.0);
+
+        // a sequential short job gains nothing from a second machine, so the long job gets three
+        let sequential = problem([1.0, 4.0]);
+        let schedule = malleable_pts(&sequential, &sequential.instance, 0.0, &Context::default());
+        assert_close(schedule.completions[0], 4.0);
+        assert_close(schedule.completions[1], 8.0 / 3.0);
+    }
+}
@@ -0,0 +1,214 @@
+//! Non-preemptive algorithms on the predictions: a job that starts runs on its machine without
+//! interruption until it completes, or, with kill-and-restart, until it is killed and loses its
+//! processing. Machines may be uniformly related; a free machine takes the next job in the
+//! order, faster machines first.
+
+use crate::{
+    instance::ProblemInstance,
+    prediction::{InstancePrediction, Prediction},
+    schedule::Schedule,
+    scheduler::{Context, Scheduler},
+    simulator::{simulate, Policy, SimJob},
+};
+
+/// Relative tolerance below which the rest of a budget counts as zero.
+const BUDGET_EPS: f64 = 1e-9;
+
+/// Non-preemptive predicted WSPT: whenever a machine is free, it starts the waiting job of
+/// largest `w_j / y_j`, which then runs to completion. Consistent, but a single job that is
+/// predicted short and turns out long blocks its machine.
+pub fn np_wspt(problem: &ProblemInstance, pred: &InstancePrediction, ctx: &Context) -> Schedule {
+    simulate(
+        problem,
+        pred,
+        &mut NonPreemptivePolicy::new(problem, pred, 1.0, false),
+        ctx,
+    )
+}
+
+/// Predicted WSPT with kill-and-restart: a job runs for a budget of `initial` times its
+/// prediction. If it has not completed by then, it is killed, its budget doubles, and it waits
+/// by `w_j` over its budget like the other jobs. With exact predictions and `initial >= 1` no
+/// job is killed and the schedule is the one of [`np_wspt`]; the doubling bounds the processing
+/// lost on wrong predictions.
+pub fn np_restart(
+    problem: &ProblemInstance,
+    pred: &InstancePrediction,
+    initial: f64,
+    ctx: &Context,
+) -> Schedule {
+    simulate(
+        problem,
+        pred,
+        &mut NonPreemptivePolicy::new(problem, pred, initial, true),
+        ctx,
+    )
+}
+
+struct NonPreemptivePolicy {
+    /// Machine speeds in non-increasing order.
+    speeds: Vec<f64>,
+    /// Processing a job may receive in its current run before it is killed, by job id.
+    budget: Vec<f64>,
+    restart: bool,
+    /// Running jobs with their machines.
+    running: Vec<(usize, usize)>,
+}
+
+impl NonPreemptivePolicy {
+    fn new(
+        problem: &ProblemInstance,
+        pred: &InstancePrediction,
+        initial: f64,
+        restart: bool,
+    ) -> Self {
+        NonPreemptivePolicy {
+            speeds: problem.machine_speeds(),
+            budget: pred.jobs.iter().map(|&y| initial * y).collect(),
+            restart,
+            running: vec![],
+        }
+    }
+
+    fn expired(&self, job: &SimJob) -> bool {
+        let budget = self.budget[job.id];
+        job.attained > 0.0 && job.attained >= budget - BUDGET_EPS * budget.max(1.0)
+    }
+}
+
+impl Policy for NonPreemptivePolicy {
+    fn killed(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<usize> {
+        if !self.restart {
+            return vec![];
+        }
+        let killed: Vec<usize> = jobs
+            .iter()
+            .filter(|job| self.running.iter().any(|&(id, _)| id == job.id) && self.expired(job))
+            .map(|job| job.id)
+            .collect();
+        for job in jobs.iter().filter(|job| killed.contains(&job.id)) {
+            self.budget[job.id] = 2.0 * job.attained;
+        }
+        self.running.retain(|(id, _)| !killed.contains(id));
+        killed
+    }
+
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        self.running
+            .retain(|&(id, _)| jobs.iter().any(|job| job.id == id));
+
+        let mut waiting: Vec<usize> = (0..jobs.len())
+            .filter(|&idx| self.running.iter().all(|&(id, _)| id != jobs[idx].id))
+            .collect();
+        waiting.sort_by(|&i, &j| {
+            (jobs[j].weight / self.budget[jobs[j].id])
+                .partial_cmp(&(jobs[i].weight / self.budget[jobs[i].id]))
+                .unwrap()
+                .then(jobs[i].id.cmp(&jobs[j].id))
+        });
+        let free: Vec<usize> = (0..self.speeds.len())
+            .filter(|&i| self.running.iter().all(|&(_, machine)| machine != i))
+            .collect();
+        for (idx, machine) in waiting.into_iter().zip(free) {
+            self.running.push((jobs[idx].id, machine));
+        }
+
+        let mut rates = vec![0.0; jobs.len()];
+        for &(id, machine) in &self.running {
+            let idx = jobs.iter().position(|job| job.id == id).unwrap();
+            rates[idx] = self.speeds[machine];
+        }
+        rates
+    }
+
+    fn horizon(&self, _time: f64, jobs: &[SimJob], rates: &[f64]) -> Option<f64> {
+        if !self.restart {
+            return None;
+        }
+        jobs.iter()
+            .zip(rates)
+            .filter(|(_, &rate)| rate > 0.0)
+            .map(|(job, &rate)| (self.budget[job.id] - job.attained).max(0.0) / rate)
+            .filter(|&dt| dt > 0.0)
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+}
+
+pub struct NpWspt;
+
+impl Scheduler for NpWspt {
+    fn name(&self) -> &str {
+        "NP-WSPT"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        np_wspt(problem, pred.lengths(), ctx)
+    }
+}
+
+pub struct NpRestart {
+    pub initial: f64,
+}
+
+impl Scheduler for NpRestart {
+    fn name(&self) -> &str {
+        "NP-WSPT (restart)"
+    }
+
+    fn param(&self) -> f64 {
+        self.initial
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        np_restart(problem, pred.lengths(), self.initial, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn restart_with_exact_predictions_is_np_wspt() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let ctx = Context {
+            record_profile: true,
+            ..Context::default()
+        };
+        for m in 1..=3 {
+            let mut problem = ProblemInstance::random(30, m, &mut rng);
+            problem.speeds = (0..m).map(|i| 1.0 / (1.0 + i as f64)).collect();
+            let wspt = np_wspt(&problem, &problem.instance, &ctx);
+            for &initial in &[1.0, 1.5, 4.0] {
+                let restart = np_restart(&problem, &problem.instance, initial, &ctx);
+                assert!(restart.lost.iter().all(|&lost| lost == 0.0));
+                for (a, b) in restart.completions.iter().zip(&wspt.completions) {
+                    assert!((a - b).abs() <= 1e-9 * b, "{} != {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn restart_doubles_the_budget() {
+        // a job of length 4 predicted as 1 is killed after 1 and 2 units of processing
+        let problem = ProblemInstance::single_machine(vec![4.0].into());
+        let schedule = np_restart(&problem, &vec![1.0].into(), 1.0, &Context::default());
+        assert_eq!(schedule.lost, vec![3.0]);
+        assert_eq!(schedule.completions, vec![7.0]);
+    }
+}
@@ -0,0 +1,317 @@
+//! Algorithms for unrelated machines (`R|r_j,pmtn|sum w_j C_j`). Every job is dispatched to a
+//! machine when it is released and stays there; each machine then shares its time among its
+//! jobs. The greedy WDEQ and PTS algorithms know how fast every job runs on every machine
+//! relative to its other machines, as usual for non-clairvoyant scheduling on unrelated
+//! machines, but not the lengths; greedy FIFO is a clairvoyant reference for the maximum flow
+//! time.
+
+use crate::{
+    instance::ProblemInstance,
+    prediction::Prediction,
+    schedule::Schedule,
+    scheduler::{Context, Scheduler},
+    simulator::{simulate, Policy, SimJob},
+};
+
+/// Non-clairvoyant greedy: a released job goes to the machine where WDEQ among the jobs already
+/// there would process it fastest, i.e. that minimises `(W_i + w_j) / s_ij` for the total weight
+/// `W_i` of the alive jobs on machine `i` and the speed `s_ij` of the job there. Every machine
+/// runs WDEQ on its jobs.
+pub fn greedy_wdeq(problem: &ProblemInstance, ctx: &Context) -> Schedule {
+    simulate(
+        problem,
+        &problem.instance,
+        &mut DispatchPolicy {
+            problem,
+            pred: None,
+            robustification: 1.0,
+            fifo: false,
+            machine: vec![None; problem.instance.len()],
+        },
+        ctx,
+    )
+}
+
+/// Greedy on the predicted lengths `pred[j][i]` of every job on every machine: a released job
+/// goes to the machine where it increases the predicted total weighted completion time under
+/// WSPT the least. Every machine runs PTS on its jobs, with WDEQ as the robust part and predicted
+/// WSPT (by the predicted lengths on that machine) as the consistent part.
+pub fn greedy_pts(
+    problem: &ProblemInstance,
+    pred: &[Vec<f64>],
+    robustification: f64,
+    ctx: &Context,
+) -> Schedule {
+    let best = pred
+        .iter()
+        .map(|row| row.iter().cloned().fold(f64::INFINITY, f64::min))
+        .collect();
+    simulate(
+        problem,
+        &best,
+        &mut DispatchPolicy {
+            problem,
+            pred: Some(pred),
+            robustification,
+            fifo: false,
+            machine: vec![None; problem.instance.len()],
+        },
+        ctx,
+    )
+}
+
+/// Clairvoyant FIFO: a released job goes to the machine where it completes first behind the
+/// jobs already there, and every machine runs its jobs in the order of their release dates.
+pub fn greedy_fifo(problem: &ProblemInstance, ctx: &Context) -> Schedule {
+    let pred = machine_predictions(problem, &problem.instance.clone().into());
+    simulate(
+        problem,
+        &problem.instance,
+        &mut DispatchPolicy {
+            problem,
+            pred: Some(&pred),
+            robustification: 0.0,
+            fifo: true,
+            machine: vec![None; problem.instance.len()],
+        },
+        ctx,
+    )
+}
+
+/// Predicted length of every job on every machine. Predicted lengths per job are scaled by the
+/// known relative speeds, so that perfect predictions give the true lengths.
+pub fn machine_predictions(problem: &ProblemInstance, pred: &Prediction) -> Vec<Vec<f64>> {
+    match pred {
+        Prediction::MachineLengths(pred) => pred.lengths.clone(),
+        _ => pred
+            .lengths()
+            .jobs
+            .iter()
+            .enumerate()
+            .map(|(j, &y)| {
+                (0..problem.m)
+                    .map(|i| y / problem.job_speed(j, i))
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+struct DispatchPolicy<'a> {
+    problem: &'a ProblemInstance,
+    /// Predicted lengths per job and machine; the dispatch is non-clairvoyant without them.
+    pred: Option<&'a [Vec<f64>]>,
+    robustification: f64,
+    /// Run the jobs of a machine in the order of their release dates instead of by predicted
+    /// WSPT, and dispatch a job to the machine where it completes first.
+    fifo: bool,
+    /// Machine of every dispatched job, by job id.
+    machine: Vec<Option<usize>>,
+}
+
+impl DispatchPolicy<'_> {
+    /// Predicted length of job `job` on its machine `i` that is still to be processed.
+    fn predicted_remaining(&self, pred: &[Vec<f64>], job: &SimJob, i: usize) -> f64 {
+        let processed = job.attained / self.problem.job_speed(job.id, i);
+        (pred[job.id][i] - processed).max(0.0)
+    }
+
+    /// Machine for the released job `jobs[new]`, given the jobs dispatched so far.
+    fn dispatch(&self, jobs: &[SimJob], new: usize) -> usize {
+        let j = jobs[new].id;
+        let weight = jobs[new].weight;
+        let mut best = (0, f64::INFINITY);
+        for i in 0..self.problem.m {
+            let on_machine = jobs.iter().filter(|job| self.machine[job.id] == Some(i));
+            let cost = match self.pred {
+                None => {
+                    let load: f64 = on_machine.map(|job| job.weight).sum();
+                    (load + weight) / self.problem.job_speed(j, i)
+                }
+                Some(pred) if self.fifo => {
+                    on_machine
+                        .map(|job| self.predicted_remaining(pred, job, i))
+                        .sum::<f64>()
+                        + pred[j][i]
+                }
+                Some(pred) => {
+                    // the new job waits for the jobs of higher predicted density and delays the
+                    // others
+                    let length = pred[j][i];
+                    let mut cost = weight * length;
+                    for job in on_machine {
+                        if job.weight / pred[job.id][i] >= weight / length {
+                            cost += weight * self.predicted_remaining(pred, job, i);
+                        } else {
+                            cost += job.weight * length;
+                        }
+                    }
+                    cost
+                }
+            };
+            if cost < best.1 {
+                best = (i, cost);
+            }
+        }
+        best.0
+    }
+}
+
+impl Policy for DispatchPolicy<'_> {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        for idx in 0..jobs.len() {
+            if self.machine[jobs[idx].id].is_none() {
+                self.machine[jobs[idx].id] = Some(self.dispatch(jobs, idx));
+            }
+        }
+
+        let mut rates = vec![0.0; jobs.len()];
+        for i in 0..self.problem.m {
+            let local: Vec<usize> = (0..jobs.len())
+                .filter(|&idx| self.machine[jobs[idx].id] == Some(i))
+                .collect();
+            let weight: f64 = local.iter().map(|&idx| jobs[idx].weight).sum();
+            let mut shares: Vec<f64> = local
+                .iter()
+                .map(|&idx| self.robustification * jobs[idx].weight / weight)
+                .collect();
+            if let Some(pred) = self.pred {
+                let density = |k: usize| jobs[local[k]].weight / pred[jobs[local[k]].id][i];
+                let release = |k: usize| self.problem.releases[jobs[local[k]].id];
+                let first = (0..local.len()).max_by(|&a, &b| {
+                    if self.fifo {
+                        release(b).partial_cmp(&release(a)).unwrap()
+                    } else {
+                        density(a).partial_cmp(&density(b)).unwrap()
+                    }
+                    .then(jobs[local[b]].id.cmp(&jobs[local[a]].id))
+                });
+                if let Some(first) = first {
+                    shares[first] += 1.0 - self.robustification;
+                }
+            }
+            for (&idx, share) in local.iter().zip(shares) {
+                rates[idx] = share * self.problem.job_speed(jobs[idx].id, i);
+            }
+        }
+        rates
+    }
+}
+
+pub struct GreedyWdeq;
+
+impl Scheduler for GreedyWdeq {
+    fn name(&self) -> &str {
+        "Greedy WDEQ"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn supports_unrelated(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        greedy_wdeq(problem, ctx)
+    }
+}
+
+pub struct GreedyPts {
+    pub robustification: f64,
+}
+
+impl Scheduler for GreedyPts {
+    fn name(&self) -> &str {
+        "Greedy PTS"
+    }
+
+    fn param(&self) -> f64 {
+        self.robustification
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn supports_unrelated(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        let pred = machine_predictions(problem, pred);
+        greedy_pts(problem, &pred, self.robustification, ctx)
+    }
+}
+
+pub struct GreedyFifo;
+
+impl Scheduler for GreedyFifo {
+    fn name(&self) -> &str {
+        "Greedy FIFO"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn supports_speeds(&self) -> bool {
+        true
+    }
+
+    fn supports_unrelated(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        greedy_fifo(problem, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    /// Two jobs released at 0 on two unrelated machines, both fastest on the first one.
+    fn problem() -> ProblemInstance {
+        ProblemInstance {
+            m: 2,
+            machine_lengths: vec![vec![2.0, 4.0], vec![3.0, 4.5]],
+            ..ProblemInstance::single_machine(vec![2.0, 3.0].into())
+        }
+    }
+
+    #[test]
+    fn greedy_dispatch_avoids_the_loaded_machine() {
+        // the first job takes the first machine. For the second job, the first machine means
+        // weight 2 at speed 1 for WDEQ and a predicted weighted completion time of 3 + 2 for
+        // PTS, the second machine weight 1 at speed 2/3 and 4.5, so it goes to the second one
+        let problem = problem();
+        let ctx = Context::default();
+        let pred = machine_predictions(&problem, &problem.instance.clone().into());
+        for schedule in &[
+            greedy_wdeq(&problem, &ctx),
+            greedy_pts(&problem, &pred, 0.0, &ctx),
+        ] {
+            assert_close(schedule.completions[0], 2.0);
+            assert_close(schedule.completions[1], 4.5);
+        }
+    }
+}
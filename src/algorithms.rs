@@ -1,12 +1,19 @@
-use rand::prelude::SliceRandom;
+use rand::{prelude::SliceRandom, Rng};
 
 use crate::{
-    instance::Instance, job::Environment, prediction::InstancePrediction, sample::create_jobs,
+    alg_identical::{compute_wdeq_rates, pts, pts_by_rank, pwspt_order, wdeq},
+    instance::{Instance, ProblemInstance},
+    job::{Environment, Job},
+    prediction::{InstancePrediction, Prediction},
+    sample::create_jobs,
+    schedule::Schedule,
+    scheduler::{Context, Scheduler},
+    simulator::{simulate, Policy, SimJob},
 };
 
 pub fn spt(instance: &Instance) -> f64 {
     let mut jobs = instance.jobs.clone();
-    jobs.sort_by(|a, b| a.partial_cmp(&b).unwrap());
+    jobs.sort_by(|a, b| a.partial_cmp(b).unwrap());
     let mut obj = 0.0;
     let mut t = 0.0;
     for j in jobs {
@@ -20,25 +27,27 @@ pub fn preferrential_rr(
     instance: &Instance,
     pred: &InstancePrediction,
     robustification: f64,
-) -> f64 {
-    instance.jobs.iter().for_each(|p| assert!(*p >= 1.0));
-    let mut jobs = create_jobs(&instance, &pred);
+    ctx: &Context,
+) -> Schedule {
+    instance.jobs.iter().for_each(|p| assert!(*p > 0.0));
+    assert!((0.0..=1.0).contains(&robustification));
+    let mut schedule = Schedule::new(instance.len(), ctx.record_profile);
+    let mut jobs = create_jobs(instance, pred);
     jobs.sort_by(|j1, j2| j1.length.partial_cmp(&j2.length).unwrap());
 
     let mut pred_order_help: Vec<(usize, f64)> =
         jobs.iter().enumerate().map(|(i, j)| (i, j.pred)).collect();
-    pred_order_help.sort_by(|(_, p1), (_, p2)| p1.partial_cmp(&p2).unwrap());
+    pred_order_help.sort_by(|(_, p1), (_, p2)| p1.partial_cmp(p2).unwrap());
     let pred_order: Vec<usize> = pred_order_help.into_iter().map(|(i, _)| i).collect();
 
     let mut n_alive = jobs.len();
     let mut pspt: usize = 0;
     let mut rr: usize = 0;
     let mut t: f64 = 0.0;
-    let mut obj: f64 = 0.0;
 
     while n_alive > 0 {
         if jobs[rr].length <= 0.0 {
-            if jobs[rr].completed == false && t > 0.0 {
+            if !jobs[rr].completed && t > 0.0 {
                 panic!("Job length 0 but not finished!")
             }
             rr += 1;
@@ -60,6 +69,23 @@ pub fn preferrential_rr(
         } else {
             l = jobs[rr].length * (n_alive as f64);
         }
+        let preferred = pred_order[pspt];
+        let share = robustification / (n_alive as f64);
+        schedule.process(
+            t,
+            t + l,
+            jobs.iter()
+                .enumerate()
+                .skip(rr)
+                .filter(|(_, job)| !job.completed)
+                .map(|(i, job)| {
+                    if i == preferred {
+                        (job.id, share + 1.0 - robustification)
+                    } else {
+                        (job.id, share)
+                    }
+                }),
+        );
         t += l;
 
         assert!(l >= 0.0);
@@ -72,10 +98,10 @@ pub fn preferrential_rr(
                     job.length -= l * robustification / (pre_n_alive as f64);
                     if job.length <= 0.0 {
                         //println!("rr completed ");
-                        if job.completed == false {
+                        if !job.completed {
                             job.completed = true;
                             n_alive -= 1;
-                            obj += t;
+                            schedule.complete(job.id, t);
                         }
                         if i == rr {
                             rr += 1;
@@ -93,21 +119,27 @@ pub fn preferrential_rr(
                     l * ((1.0 - robustification) + (robustification / (pre_n_alive as f64)));
             }
             if jobs[pred_order[pspt]].length <= 0.0 {
-                if jobs[pred_order[pspt]].completed == false {
+                if !jobs[pred_order[pspt]].completed {
                     jobs[pred_order[pspt]].completed = true;
                     n_alive -= 1;
-                    obj += t;
+                    schedule.complete(jobs[pred_order[pspt]].id, t);
                 }
                 pspt += 1;
             }
         }
     }
 
-    return obj;
+    schedule
 }
 
-pub fn two_stage_schedule(instance: &Instance, pred: &InstancePrediction, lambda: f64) -> f64 {
-    let mut jobs = create_jobs(&instance, &pred);
+pub fn two_stage_schedule(
+    instance: &Instance,
+    pred: &InstancePrediction,
+    lambda: f64,
+    ctx: &Context,
+) -> Schedule {
+    let mut schedule = Schedule::new(instance.len(), ctx.record_profile);
+    let mut jobs = create_jobs(instance, pred);
     jobs.sort_by(|j1, j2| j1.length.partial_cmp(&j2.length).unwrap());
 
     let opt_y = spt(pred);
@@ -115,7 +147,6 @@ pub fn two_stage_schedule(instance: &Instance, pred: &InstancePrediction, lambda
     let mut n_alive = jobs.len();
     let mut rr: usize = 0;
     let mut t: f64 = 0.0;
-    let mut obj: f64 = 0.0;
 
     let mut misprediction_detected = false;
 
@@ -126,7 +157,7 @@ pub fn two_stage_schedule(instance: &Instance, pred: &InstancePrediction, lambda
     while n_alive > 0 && !misprediction_detected && t < max_rr {
         assert!(rr < jobs.len());
         if jobs[rr].length <= 0.0 {
-            if jobs[rr].completed == false && t > 0.0 {
+            if !jobs[rr].completed && t > 0.0 {
                 panic!("Job length 0 but not finished!")
             }
             rr += 1;
@@ -134,15 +165,16 @@ pub fn two_stage_schedule(instance: &Instance, pred: &InstancePrediction, lambda
         }
 
         let l = (jobs[rr].length * (n_alive as f64)).min(max_rr - t);
+        process_rr(&mut schedule, &jobs[rr..], t, l, n_alive);
         t += l;
         let pre_n_alive = n_alive;
         for (i, job) in jobs.iter_mut().enumerate().skip(rr) {
             job.length -= l / (pre_n_alive as f64);
             if job.length <= 0.0 {
-                if job.completed == false {
+                if !job.completed {
                     job.completed = true;
                     n_alive -= 1;
-                    obj += t;
+                    schedule.complete(job.id, t);
 
                     if instance[job.id] != pred[job.id] {
                         misprediction_detected = true;
@@ -162,8 +194,9 @@ pub fn two_stage_schedule(instance: &Instance, pred: &InstancePrediction, lambda
     let mut idx = 0;
     while !misprediction_detected && idx < jobs.len() {
         if !jobs[idx].completed {
+            schedule.process(t, t + jobs[idx].length, [(jobs[idx].id, 1.0)]);
             t += jobs[idx].length;
-            obj += t;
+            schedule.complete(jobs[idx].id, t);
             jobs[idx].length = 0.0;
             jobs[idx].completed = true;
             n_alive -= 1;
@@ -180,7 +213,7 @@ pub fn two_stage_schedule(instance: &Instance, pred: &InstancePrediction, lambda
     while n_alive > 0 {
         assert!(rr < jobs.len());
         if jobs[rr].length <= 0.0 {
-            if jobs[rr].completed == false && t > 0.0 {
+            if !jobs[rr].completed && t > 0.0 {
                 panic!("Job length 0 but not finished!")
             }
             rr += 1;
@@ -188,15 +221,16 @@ pub fn two_stage_schedule(instance: &Instance, pred: &InstancePrediction, lambda
         }
 
         let l = jobs[rr].length * (n_alive as f64);
+        process_rr(&mut schedule, &jobs[rr..], t, l, n_alive);
         t += l;
         let pre_n_alive = n_alive;
         for (i, job) in jobs.iter_mut().enumerate().skip(rr) {
             job.length -= l / (pre_n_alive as f64);
             if job.length <= 0.0 {
-                if job.completed == false {
+                if !job.completed {
                     job.completed = true;
                     n_alive -= 1;
-                    obj += t;
+                    schedule.complete(job.id, t);
                 }
                 if i == rr {
                     rr += 1;
@@ -205,89 +239,266 @@ pub fn two_stage_schedule(instance: &Instance, pred: &InstancePrediction, lambda
         }
     }
 
-    obj
+    schedule
 }
 
-pub fn phase_algorithm(instance: &Instance, pred: &InstancePrediction, epsilon: f64) -> f64 {
-    let jobs = create_jobs(&instance, &pred);
+/// TwoStage for weights, release dates and `m` machines: WDEQ until it has run for the budget
+/// of [`two_stage_schedule`] computed on the jobs released so far, then P-WSPT on the
+/// predicted lengths. Once a completed job reveals a wrong prediction, it falls back to WDEQ
+/// for good.
+pub fn weighted_two_stage(
+    problem: &ProblemInstance,
+    pred: &InstancePrediction,
+    lambda: f64,
+    ctx: &Context,
+) -> Schedule {
+    let mut policy = TwoStagePolicy {
+        m: problem.m,
+        lambda,
+        stage: Stage::RoundRobin,
+        seen: vec![false; problem.instance.len()],
+        released: vec![],
+        budget: 0.0,
+        rr_time: 0.0,
+        last_time: None,
+        alive: 0,
+    };
+    simulate(problem, pred, &mut policy, ctx)
+}
 
-    let mut env = Environment::new(jobs);
-    let delta = 1.0 / 50.0;
+/// Relative tolerance below which the rest of the round robin stage counts as zero.
+const STAGE_EPS: f64 = 1e-9;
 
-    // line 2
-    while env.nk() as f64 >= (env.n as f64).log2() / (epsilon * epsilon * epsilon) {
-        // line 3:
-        let mk = median_est(&mut env, delta);
-
-        // line 4:
-        let error = error_est(&mut env, epsilon, mk);
-
-        // line 5:
-        if error >= (epsilon * (delta * delta) * mk * env.nk() as f64 * env.nk() as f64) / 16.0 {
-            //line 6:
-            env.jobs
-                .sort_by(|j1, j2| j1.length.partial_cmp(&j2.length).unwrap());
-            let mut rr_per_job = 0.0;
-            let mut finished = 0;
-            for j in 0..env.nk() {
-                if env.process(j, rr_per_job) {
-                    finished += 1;
-                } else {
-                    let amount = env.jobs[j].length.min(2.0 * mk - rr_per_job);
-                    let l = amount * (env.nk() - finished) as f64;
-                    env.run_for(l);
-                    if env.process(j, amount) {
-                        finished += 1;
-                    }
-                    rr_per_job += amount;
-                }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    RoundRobin,
+    Predicted,
+    Fallback,
+}
+
+struct TwoStagePolicy {
+    m: usize,
+    lambda: f64,
+    stage: Stage,
+    seen: Vec<bool>,
+    /// Weights and predictions of all jobs released so far.
+    released: Vec<(f64, f64)>,
+    budget: f64,
+    /// Time during which jobs were alive since the start, which is the time spent in the round
+    /// robin stage as long as it lasts. It counts wall time rather than processed work, which
+    /// falls behind the rates under switching costs.
+    rr_time: f64,
+    /// Time up to which `rr_time` is counted, `None` while no job is alive.
+    last_time: Option<f64>,
+    alive: usize,
+}
+
+impl TwoStagePolicy {
+    fn count_time(&mut self, time: f64) {
+        if let Some(last) = self.last_time {
+            self.rr_time += time - last;
+        }
+        self.last_time = Some(time);
+    }
+}
+
+impl Policy for TwoStagePolicy {
+    fn rates(&mut self, time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        self.count_time(time);
+        self.alive = jobs.len();
+
+        let mut new_jobs = false;
+        for job in jobs {
+            if !self.seen[job.id] {
+                self.seen[job.id] = true;
+                self.released.push((job.weight, job.pred));
+                new_jobs = true;
             }
-            env.clear_completed();
-        } else {
-            //line 8:
-            env.jobs
-                .sort_by(|j1, j2| j1.pred.partial_cmp(&j2.pred).unwrap());
-            for j in 0..env.nk() {
-                if env.jobs[j].pred <= (1.0 + epsilon) * mk {
-                    let l = env.jobs[j]
-                        .length
-                        .min(env.jobs[j].pred + 3.0 * epsilon * mk);
-                    env.run_for(l);
-                    env.process(j, l);
+        }
+        if new_jobs {
+            self.budget = two_stage_budget(self.lambda, &self.released, self.m);
+        }
+        if self.stage == Stage::RoundRobin
+            && self.rr_time >= self.budget - STAGE_EPS * self.budget.max(1.0)
+        {
+            self.stage = Stage::Predicted;
+        }
+
+        match self.stage {
+            Stage::Predicted => {
+                let mut rates = vec![0.0; jobs.len()];
+                for idx in pwspt_order(jobs).into_iter().take(self.m) {
+                    rates[idx] = 1.0;
                 }
+                rates
             }
-            env.clear_completed();
+            _ => compute_wdeq_rates(jobs, self.m),
         }
     }
 
-    // line 10:
-    env.jobs
-        .sort_by(|j1, j2| j1.length.partial_cmp(&j2.length).unwrap());
+    fn horizon(&self, _time: f64, _jobs: &[SimJob], _rates: &[f64]) -> Option<f64> {
+        if self.stage == Stage::RoundRobin {
+            Some(self.budget - self.rr_time)
+        } else {
+            None
+        }
+    }
 
-    let mut rr_per_job = 0.0;
-    let mut finished = 0;
-    for j in 0..env.nk() {
-        if env.process(j, rr_per_job) {
-            finished += 1;
+    fn completed(&mut self, time: f64, job: &SimJob) {
+        self.count_time(time);
+        self.alive -= 1;
+        if self.alive == 0 {
+            self.last_time = None;
+        }
+        if (job.attained - job.pred).abs() > 1e-6 * job.pred.max(1.0) {
+            self.stage = Stage::Fallback;
+        }
+    }
+}
+
+/// Length of the round robin stage, `lambda * W * OPT_y / sum_{i<j} w_i w_j` for the
+/// `(weight, prediction)` pairs of the known jobs, where `OPT_y` list schedules the predicted
+/// lengths in WSPT order on `m` machines; for a single machine and unit weights this is
+/// `lambda * n * OPT_y / binom(n, 2)`.
+fn two_stage_budget(lambda: f64, jobs: &[(f64, f64)], m: usize) -> f64 {
+    let mut order = jobs.to_vec();
+    order.sort_by(|(w1, y1), (w2, y2)| (y1 / w1).partial_cmp(&(y2 / w2)).unwrap());
+    let mut free = vec![0.0; m];
+    let mut opt_y = 0.0;
+    for (w, y) in order {
+        let machine = (0..m)
+            .min_by(|&a, &b| free[a].partial_cmp(&free[b]).unwrap())
+            .unwrap();
+        free[machine] += y;
+        opt_y += w * free[machine];
+    }
+    let total: f64 = jobs.iter().map(|(w, _)| w).sum();
+    let pairs = (total * total - jobs.iter().map(|(w, _)| w * w).sum::<f64>()) / 2.0;
+    lambda * total * opt_y / pairs
+}
+
+/// Records round robin among the uncompleted `jobs` for `l` time units from `t` on.
+fn process_rr(schedule: &mut Schedule, jobs: &[Job], t: f64, l: f64, n_alive: usize) {
+    let share = 1.0 / (n_alive as f64);
+    schedule.process(
+        t,
+        t + l,
+        jobs.iter()
+            .filter(|job| !job.completed)
+            .map(|job| (job.id, share)),
+    );
+}
+
+/// The phase algorithm of Im et al. Weighted jobs are handled by comparing lengths per unit
+/// of weight and sharing the machines in proportion to the weights; on `m` machines the
+/// round robin steps become WDEQ and predicted orders are list scheduled. Released jobs join
+/// at the next phase, and the final round robin is interrupted by releases.
+pub fn phase_algorithm(
+    problem: &ProblemInstance,
+    pred: &InstancePrediction,
+    epsilon: f64,
+    ctx: &Context,
+) -> Schedule {
+    let n = problem.instance.len();
+    let mut pending: Vec<usize> = (0..n).collect();
+    pending.sort_by(|&i, &j| {
+        problem.releases[j]
+            .partial_cmp(&problem.releases[i])
+            .unwrap()
+    });
+
+    let schedule = Schedule::new(n, ctx.record_profile);
+    let mut env = Environment::new(vec![], problem.m, 0.0, schedule);
+    let delta = 1.0 / 50.0;
+    let mut rng = ctx.rng();
+    loop {
+        while let Some(&j) = pending.last() {
+            if problem.releases[j] > env.time {
+                break;
+            }
+            env.admit(Job {
+                weight: problem.weights[j],
+                ..Job::new(j, problem.instance[j], pred[j])
+            });
+            pending.pop();
+        }
+        let next_release = pending.last().map(|&j| problem.releases[j]);
+        if env.nk() == 0 {
+            match next_release {
+                Some(release) => {
+                    env.time = release;
+                    continue;
+                }
+                None => break,
+            }
+        }
+
+        // line 2
+        if env.nk() as f64 >= (env.n as f64).log2() / (epsilon * epsilon * epsilon) {
+            phase(&mut env, epsilon, delta, &mut rng);
         } else {
-            let amount = env.jobs[j].length;
-            let l = amount * (env.nk() - finished) as f64;
-            env.run_for(l);
-            env.complete(j);
-            finished += 1;
-            rr_per_job += amount;
+            // line 10:
+            let targets: Vec<(usize, f64, f64)> = env
+                .jobs
+                .iter()
+                .enumerate()
+                .map(|(j, job)| (j, job.length, job.weight))
+                .collect();
+            env.share(&targets, next_release.unwrap_or(f64::INFINITY));
+            env.clear_completed();
         }
     }
-    env.clear_completed();
-    assert_eq!(env.nk(), 0);
-    return env.obj;
+    env.schedule
 }
 
-fn median_est(env: &mut Environment, delta: f64) -> f64 {
+fn phase<R: Rng>(env: &mut Environment, epsilon: f64, delta: f64, rng: &mut R) {
+    // line 3:
+    let mk = median_est(env, delta, rng);
+
+    // line 4:
+    let error = error_est(env, epsilon, mk, rng);
+
+    // line 5:
+    if error >= (epsilon * (delta * delta) * mk * env.nk() as f64 * env.nk() as f64) / 16.0 {
+        //line 6: round robin until every job received 2 mk per unit of weight
+        let targets: Vec<(usize, f64, f64)> = env
+            .jobs
+            .iter()
+            .enumerate()
+            .map(|(j, job)| (j, job.length.min(2.0 * mk * job.weight), job.weight))
+            .collect();
+        env.share(&targets, f64::INFINITY);
+        env.clear_completed();
+    } else {
+        //line 8:
+        env.jobs.sort_by(|j1, j2| {
+            (j1.pred / j1.weight)
+                .partial_cmp(&(j2.pred / j2.weight))
+                .unwrap()
+        });
+        let targets: Vec<(usize, f64)> = env
+            .jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| job.pred <= (1.0 + epsilon) * mk * job.weight)
+            .map(|(j, job)| {
+                (
+                    j,
+                    job.length.min(job.pred + 3.0 * epsilon * mk * job.weight),
+                )
+            })
+            .collect();
+        env.run_in_order(&targets);
+        env.clear_completed();
+    }
+}
+
+fn median_est<R: Rng>(env: &mut Environment, delta: f64, rng: &mut R) -> f64 {
     // line 1:
     let sample_size = ((2.0 * env.n as f64).ln() / (delta * delta)).ceil() as usize;
     let indices = (0..env.nk()).collect::<Vec<usize>>();
-    let mut sample: Vec<&usize> = sample_with_replacement(&indices, sample_size);
+    let mut sample: Vec<&usize> =
+        sample_with_replacement(&indices, sample_size, |&k| env.jobs[k].weight, rng);
     let max_job_index = **sample.iter().max().unwrap();
 
     // how often does a job occur in sample
@@ -296,47 +507,47 @@ fn median_est(env: &mut Environment, delta: f64) -> f64 {
         occurences[idx] += 1;
     }
 
-    // sort the jobs in the sample in the order of their completion by RR
+    // sort the jobs in the sample in the order of their completion by RR, ties by index so
+    // that duplicates are adjacent
     sample.sort_by(|&i, &j| {
         (env.jobs[*i].length / occurences[*i] as f64)
             .partial_cmp(&(env.jobs[*j].length / occurences[*j] as f64))
             .unwrap()
+            .then(i.cmp(j))
     });
 
     // remove duplicatd
     sample.dedup();
 
-    let initial_lengths: Vec<f64> = sample.iter().map(|j| env.jobs[**j].length).collect();
-
-    // line 2:
-    let mut rr_per_job = 0.0;
-
-    // count completed job for break condition
+    // line 2: round robin over the sample, each job weighted by its occurences, until half of
+    // the sample completed
     let mut finished = 0;
+    let median = sample
+        .iter()
+        .position(|&&k| {
+            finished += occurences[k];
+            2 * finished >= sample_size
+        })
+        .expect("Median estimation did not work properly!");
+    let median_job = &env.jobs[*sample[median]];
+    let per_occurence = median_job.length / occurences[*sample[median]] as f64;
+    let median_ratio = median_job.length / median_job.weight;
+
+    let targets: Vec<(usize, f64, f64)> = sample
+        .iter()
+        .map(|&&k| {
+            let occ = occurences[k] as f64;
+            (k, env.jobs[k].length.min(per_occurence * occ), occ)
+        })
+        .collect();
+    env.share(&targets, f64::INFINITY);
 
-    for (i, &job_idx) in sample.into_iter().enumerate() {
-        if env.process(job_idx, rr_per_job * occurences[job_idx] as f64) {
-            finished += occurences[job_idx];
-        } else {
-            let amount = env.jobs[job_idx].length;
-            let l = amount * (sample_size - finished) as f64 / occurences[job_idx] as f64;
-            env.run_for(l);
-            env.complete(job_idx);
-            finished += occurences[job_idx];
-            rr_per_job += amount / occurences[job_idx] as f64;
-        }
-
-        if 2 * finished >= sample_size {
-            // line 3
-            env.clear_completed();
-            return initial_lengths[i];
-        }
-    }
-
-    panic!("Median estimation did not work properly!");
+    // line 3
+    env.clear_completed();
+    median_ratio
 }
 
-fn error_est(env: &mut Environment, epsilon: f64, est_median: f64) -> f64 {
+fn error_est<R: Rng>(env: &mut Environment, epsilon: f64, est_median: f64, rng: &mut R) -> f64 {
     // line 1:
     let sample_size = ((env.n as f64).log2() / (epsilon * epsilon)).ceil() as usize;
     let mut indices = Vec::<(usize, usize)>::new();
@@ -345,7 +556,7 @@ fn error_est(env: &mut Environment, epsilon: f64, est_median: f64) -> f64 {
             indices.push((i, j));
         }
     }
-    let sample = sample_with_replacement(&indices, sample_size);
+    let sample = sample_with_replacement(&indices, sample_size, |_| 1.0, rng);
     let mut job_sample: Vec<usize> = sample
         .iter()
         .flat_map(|(i, j)| vec![*i, *j].into_iter())
@@ -355,13 +566,15 @@ fn error_est(env: &mut Environment, epsilon: f64, est_median: f64) -> f64 {
 
     // line 2:
     let mut d = vec![0.0; env.nk() + 1];
-    let max_l = (1.0 + epsilon) * est_median;
+    let mut targets = vec![];
     for job_idx in job_sample {
+        let weight = env.jobs[job_idx].weight;
+        let max_l = (1.0 + epsilon) * est_median * weight;
         let l = env.jobs[job_idx].length.min(max_l);
-        d[job_idx] = (l - env.jobs[job_idx].pred.min(max_l)).abs();
-        env.run_for(l);
-        env.process(job_idx, l);
+        d[job_idx] = (l - env.jobs[job_idx].pred.min(max_l)).abs() / weight;
+        targets.push((job_idx, l));
     }
+    env.run_in_order(&targets);
     env.clear_completed();
 
     // line 3:
@@ -373,15 +586,121 @@ fn error_est(env: &mut Environment, epsilon: f64, est_median: f64) -> f64 {
         / (sample_size as f64)
 }
 
-fn sample_with_replacement<T>(set: &[T], sample_size: usize) -> Vec<&T> {
+/// Samples elements with probability proportional to `weight`.
+fn sample_with_replacement<'a, T, F: Fn(&T) -> f64, R: Rng>(
+    set: &'a [T],
+    sample_size: usize,
+    weight: F,
+    rng: &mut R,
+) -> Vec<&'a T> {
     if set.is_empty() {
         return vec![];
     }
-    let mut rng = rand::thread_rng();
     let mut index_sample = Vec::<&T>::with_capacity(sample_size);
     for _ in 0..sample_size {
-        let index = set.choose(&mut rng).unwrap();
+        let index = set.choose_weighted(rng, &weight).unwrap();
         index_sample.push(index);
     }
-    return index_sample;
+    index_sample
+}
+
+pub struct PreferentialRoundRobin {
+    pub robustification: f64,
+}
+
+impl Scheduler for PreferentialRoundRobin {
+    fn name(&self) -> &str {
+        "PRR"
+    }
+
+    fn param(&self) -> f64 {
+        self.robustification
+    }
+
+    fn accepts_permutations(&self) -> bool {
+        true
+    }
+
+    /// Only the predicted order matters, so a permutation is followed through its ranks.
+    /// Beyond the single machine setting, or with switching costs, this is PTS, whose robust
+    /// part is weighted round robin and whose consistent part is WSPT on the predicted lengths,
+    /// or the predicted order for a permutation.
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        let keys = pred.order_keys();
+        if problem.is_single_machine() && ctx.switching.is_none() {
+            preferrential_rr(&problem.instance, &keys, self.robustification, ctx)
+        } else if let Prediction::Permutation(_) = pred {
+            pts_by_rank(problem, &keys, self.robustification, ctx)
+        } else {
+            pts(problem, &keys, self.robustification, ctx)
+        }
+    }
+}
+
+pub struct RoundRobin;
+
+impl Scheduler for RoundRobin {
+    fn name(&self) -> &str {
+        "Round-Robin"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        if problem.is_single_machine() && ctx.switching.is_none() {
+            preferrential_rr(&problem.instance, &problem.instance, 1.0, ctx)
+        } else {
+            wdeq(problem, ctx)
+        }
+    }
+}
+
+pub struct TwoStage {
+    pub lambda: f64,
+}
+
+impl Scheduler for TwoStage {
+    fn name(&self) -> &str {
+        "TwoStage"
+    }
+
+    fn param(&self) -> f64 {
+        self.lambda
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        if problem.is_single_machine() && ctx.switching.is_none() {
+            two_stage_schedule(&problem.instance, pred.lengths(), self.lambda, ctx)
+        } else {
+            weighted_two_stage(problem, pred.lengths(), self.lambda, ctx)
+        }
+    }
+}
+
+pub struct PhaseAlgorithm {
+    pub epsilon: f64,
+}
+
+impl Scheduler for PhaseAlgorithm {
+    fn name(&self) -> &str {
+        "Im et al."
+    }
+
+    fn param(&self) -> f64 {
+        self.epsilon
+    }
+
+    fn supports_switch_cost(&self) -> bool {
+        false
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        phase_algorithm(problem, pred.lengths(), self.epsilon, ctx)
+    }
 }
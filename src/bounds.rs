@@ -0,0 +1,230 @@
+//! Lower bounds on the optimal `sum w_j C_j` of `P|r_j,pmtn|sum w_j C_j` (and its related
+//! machines variant `Q|r_j,pmtn|sum w_j C_j` and malleable jobs with speed-up curves) for
+//! instances that are too large for the exact solver in [`crate::opt`].
+
+use crate::instance::ProblemInstance;
+
+/// Every job completes at least its length (at its largest rate) after its release.
+pub fn release_plus_length(problem: &ProblemInstance) -> f64 {
+    (0..problem.instance.len())
+        .map(|j| {
+            problem.weights[j] * (problem.releases[j] + problem.instance[j] / problem.max_rate(j))
+        })
+        .sum()
+}
+
+/// Single machine of speed `m` (the total speed): processing in every instant the work the
+/// machines do turns any schedule into one for the fast machine in which no job completes
+/// later (speed-up curves never process more than one unit of work per machine and time). SRPT
+/// is optimal there for unit (or equal) weights; with unequal weights there is no bound, as the
+/// mean-busy-time bound of the fast machine is never tighter than [`mean_busy_time`].
+pub fn fast_machine(problem: &ProblemInstance) -> Option<f64> {
+    let equal_weights = problem.weights.windows(2).all(|w| w[0] == w[1]);
+    if !equal_weights {
+        return None;
+    }
+    let runs = fast_machine_schedule(problem, |_, remaining| remaining);
+    Some(
+        runs.iter()
+            .zip(&problem.weights)
+            .map(|(run, w)| w * run.completion)
+            .sum(),
+    )
+}
+
+/// Mean-busy-time bound. The mean busy time of a job is the same in an `m`-machine schedule and
+/// in its fast single machine counterpart, and it is at most `C_j - p_j / (2s)` since a job runs
+/// at rate at most `s`, the fastest speed (1 on identical machines) or the rate of a malleable
+/// job on all machines. The mean-busy-time LP relaxation of the fast machine is solved by
+/// preemptive WSPT (Goemans, 1997), so no LP has to be set up.
+pub fn mean_busy_time(problem: &ProblemInstance) -> f64 {
+    wspt_mean_busy_times(problem)
+        .iter()
+        .enumerate()
+        .map(|(j, mean)| {
+            problem.weights[j] * (mean + problem.instance[j] / (2.0 * problem.max_rate(j)))
+        })
+        .sum()
+}
+
+/// The tightest of the lower bounds above.
+pub fn lower_bound(problem: &ProblemInstance) -> f64 {
+    release_plus_length(problem)
+        .max(fast_machine(problem).unwrap_or(0.0))
+        .max(mean_busy_time(problem))
+}
+
+/// Lower bound on the maximum flow time: FIFO minimises it on the fast machine, and no job
+/// completes faster than its length at its largest rate.
+pub fn max_flow(problem: &ProblemInstance) -> f64 {
+    let longest = (0..problem.instance.len())
+        .map(|j| problem.instance[j] / problem.max_rate(j))
+        .fold(0.0, f64::max);
+    fast_machine_schedule(problem, |j, _| problem.releases[j])
+        .iter()
+        .zip(&problem.releases)
+        .map(|(run, r)| run.completion - r)
+        .fold(longest, f64::max)
+}
+
+/// Mean busy times of preemptive WSPT on the fast machine.
+fn wspt_mean_busy_times(problem: &ProblemInstance) -> Vec<f64> {
+    let ratios: Vec<f64> = (0..problem.instance.len())
+        .map(|j| problem.instance[j] / problem.weights[j])
+        .collect();
+    fast_machine_schedule(problem, |j, _| ratios[j])
+        .iter()
+        .map(|run| run.mean_busy_time)
+        .collect()
+}
+
+struct Run {
+    completion: f64,
+    mean_busy_time: f64,
+}
+
+/// Preemptive schedule on the fast machine that always runs the released job with the smallest
+/// `priority(job, remaining length)`. Priorities may only change through the running job's own
+/// processing and must not increase by it.
+fn fast_machine_schedule<F: Fn(usize, f64) -> f64>(
+    problem: &ProblemInstance,
+    priority: F,
+) -> Vec<Run> {
+    let n = problem.instance.len();
+    let speed = problem.total_speed();
+    let mut pending: Vec<usize> = (0..n).collect();
+    pending.sort_by(|&i, &j| {
+        problem.releases[j]
+            .partial_cmp(&problem.releases[i])
+            .unwrap()
+    });
+
+    let mut remaining: Vec<f64> = problem.instance.jobs.iter().map(|p| p / speed).collect();
+    let mut busy_moment = vec![0.0; n];
+    let mut completions = vec![0.0; n];
+    let mut alive: Vec<usize> = vec![];
+    let mut t = 0.0;
+    while !alive.is_empty() || !pending.is_empty() {
+        while let Some(&j) = pending.last() {
+            if problem.releases[j] > t {
+                break;
+            }
+            alive.push(j);
+            pending.pop();
+        }
+        let next_release = pending.last().map(|&j| problem.releases[j]);
+        let idx = match (0..alive.len()).min_by(|&a, &b| {
+            priority(alive[a], remaining[alive[a]])
+                .partial_cmp(&priority(alive[b], remaining[alive[b]]))
+                .unwrap()
+        }) {
+            Some(idx) => idx,
+            None => {
+                t = next_release.unwrap();
+                continue;
+            }
+        };
+        let j = alive[idx];
+        let dt = match next_release {
+            Some(release) => remaining[j].min(release - t),
+            None => remaining[j],
+        };
+        busy_moment[j] += dt * (t + dt / 2.0);
+        remaining[j] -= dt;
+        t += dt;
+        if remaining[j] <= 0.0 {
+            completions[j] = t;
+            alive.swap_remove(idx);
+        }
+    }
+
+    (0..n)
+        .map(|j| Run {
+            completion: completions[j],
+            mean_busy_time: busy_moment[j] * speed / problem.instance[j],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        opt::optimum,
+        scheduler::{AlgorithmSpec, Context, Registry},
+    };
+
+    fn optimum_value(problem: &ProblemInstance) -> f64 {
+        optimum(problem, &Context::default()).weighted_completion_time(&problem.weights)
+    }
+
+    fn assert_below(bound: f64, value: f64) {
+        assert!(bound <= value * (1.0 + 1e-9), "{} > {}", bound, value);
+    }
+
+    /// Random problems of 6 jobs on 1 to 3 machines: weighted with release dates, and the same
+    /// with unit weights (where the fast machine bound applies) and with all jobs released at 0.
+    fn problems() -> Vec<ProblemInstance> {
+        let mut rng = StdRng::seed_from_u64(2);
+        (1..=3)
+            .flat_map(|m| (0..5).map(move |_| m))
+            .flat_map(|m| {
+                let weighted = ProblemInstance::random(6, m, &mut rng);
+                vec![
+                    ProblemInstance {
+                        weights: vec![1.0; 6],
+                        ..weighted.clone()
+                    },
+                    ProblemInstance {
+                        releases: vec![0.0; 6],
+                        ..weighted.clone()
+                    },
+                    weighted,
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lower_bound_is_at_most_the_optimum() {
+        for problem in problems() {
+            assert_below(lower_bound(&problem), optimum_value(&problem));
+        }
+    }
+
+    #[test]
+    fn lower_bound_on_related_machines() {
+        let registry = Registry::default();
+        for problem in problems() {
+            // machines of equal speed 2 finish every schedule of the identical machines with the
+            // lengths halved at the same time
+            let fast = ProblemInstance {
+                speeds: vec![2.0; problem.m],
+                ..problem.clone()
+            };
+            let halved = ProblemInstance {
+                instance: problem.instance.jobs.iter().map(|p| p / 2.0).collect(),
+                ..problem.clone()
+            };
+            assert_below(lower_bound(&fast), optimum_value(&halved));
+
+            // with different speeds, below every schedule of the algorithms for related machines
+            let related = ProblemInstance {
+                speeds: (0..problem.m).map(|i| 1.0 + i as f64).collect(),
+                ..problem
+            };
+            let pred = related.instance.clone().into();
+            for key in &["pwspt", "wdeq", "srpt", "fifo", "np-wspt"] {
+                let alg = registry
+                    .create(&key.parse::<AlgorithmSpec>().unwrap())
+                    .unwrap();
+                let value = alg
+                    .schedule(&related, &pred, &Context::default())
+                    .weighted_completion_time(&related.weights);
+                assert_below(lower_bound(&related), value);
+            }
+        }
+    }
+}
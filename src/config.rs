@@ -0,0 +1,247 @@
+//! Experiment specifications in TOML or JSON files, run by the `run` subcommand. A
+//! specification is translated into the command line of its experiment, so every setting keeps
+//! the default and the validation of the corresponding option.
+
+use std::{fs, path::Path, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Subcommand to run: `exp1` to `exp5`, `trace` or `generate`
+    pub experiment: String,
+    pub output: Option<PathBuf>,
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub validate: bool,
+    /// Error measures to report, as for `--errors`
+    #[serde(default)]
+    pub errors: Vec<String>,
+    /// Objective as for `--objective`
+    pub objective: Option<String>,
+    /// Switching cost and time slice as for `--switch-cost` and `--quantum`
+    pub switch_cost: Option<f64>,
+    pub quantum: Option<f64>,
+    /// Reference reported as `opt` by exp3
+    pub opt: Option<String>,
+    #[serde(default)]
+    pub instances: InstanceConfig,
+    #[serde(default)]
+    pub predictions: PredictionConfig,
+    /// Algorithms with their parameter grids; the experiment's default set if empty
+    #[serde(default)]
+    pub algorithms: Vec<AlgorithmConfig>,
+    /// Grid of the learned PRR of exp2 (`--learn`)
+    #[serde(default)]
+    pub learn: Vec<f64>,
+}
+
+/// Instance generator, or the file to read the instances from.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InstanceConfig {
+    pub count: Option<usize>,
+    pub jobs: Option<usize>,
+    pub machines: Option<usize>,
+    /// Machine speeds of exp3 (`--speeds`), one per machine
+    #[serde(default)]
+    pub speeds: Vec<f64>,
+    /// Distribution of the machine factors of unrelated machines in exp3 (`--unrelated`)
+    pub unrelated: Option<String>,
+    /// Speed-up curves of malleable jobs in exp3 (`--speedup`)
+    pub speedup: Option<String>,
+    /// Distributions as for `--length-dist`, `--weight-dist` and `--release-dist`
+    pub lengths: Option<String>,
+    pub weights: Option<String>,
+    pub releases: Option<String>,
+    /// Problem file for `--instances`, or the SWF trace of the `trace` experiment
+    pub file: Option<PathBuf>,
+}
+
+/// Prediction model and noise sweep.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PredictionConfig {
+    /// Noise proportional to the square root of the length (`--rel-sigma`)
+    #[serde(default)]
+    pub relative: bool,
+    /// Requested times of the trace as predictions (`--requested`)
+    #[serde(default)]
+    pub requested: bool,
+    pub per_sigma: Option<usize>,
+    pub base_sigma: Option<f64>,
+    pub step_sigma: Option<f64>,
+    pub num_sigma: Option<i32>,
+    /// Fixed noise and number of rounds of exp2
+    pub sigma: Option<f64>,
+    pub rounds: Option<usize>,
+    /// Spreads and sample count of the predicted distributions of exp5
+    #[serde(default)]
+    pub spread: Vec<f64>,
+    pub samples: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlgorithmConfig {
+    pub key: String,
+    /// One run per parameter; none for algorithms without a parameter
+    #[serde(default)]
+    pub params: Vec<f64>,
+}
+
+impl Config {
+    /// Reads a JSON file if the name ends in `.json`, TOML otherwise.
+    pub fn read(path: &Path) -> Result<Config> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("cannot read config {}", path.display()))?;
+        let config = if path.extension() == Some("json".as_ref()) {
+            serde_json::from_str(&content)?
+        } else {
+            toml::from_str(&content)?
+        };
+        Ok(config)
+    }
+
+    /// The subcommand and its options, without the global ones.
+    pub fn args(&self) -> Result<Vec<String>> {
+        if self.experiment == "run" {
+            bail!("a config cannot run another config");
+        }
+        let mut args = vec![self.experiment.clone()];
+        let instances = &self.instances;
+        let predictions = &self.predictions;
+        let file = instances.file.as_ref().map(|f| f.display().to_string());
+        if self.experiment == "trace" {
+            args.extend(file);
+        } else {
+            option(&mut args, "--instances", file);
+        }
+        option(&mut args, "-n", instances.count);
+        option(&mut args, "-l", instances.jobs);
+        option(&mut args, "-m", instances.machines);
+        if !instances.speeds.is_empty() {
+            let speeds: Vec<String> = instances.speeds.iter().map(|s| s.to_string()).collect();
+            option(&mut args, "--speeds", Some(speeds.join(",")));
+        }
+        option(&mut args, "--unrelated", instances.unrelated.as_ref());
+        option(&mut args, "--speedup", instances.speedup.as_ref());
+        option(&mut args, "--length-dist", instances.lengths.as_ref());
+        option(&mut args, "--weight-dist", instances.weights.as_ref());
+        option(&mut args, "--release-dist", instances.releases.as_ref());
+        option(&mut args, "-p", predictions.per_sigma);
+        option(&mut args, "--base-sigma", predictions.base_sigma);
+        option(&mut args, "--step-sigma", predictions.step_sigma);
+        option(&mut args, "--num-sigma", predictions.num_sigma);
+        option(&mut args, "-s", predictions.sigma);
+        option(&mut args, "-t", predictions.rounds);
+        option(&mut args, "-k", predictions.samples);
+        if !predictions.spread.is_empty() {
+            let spreads: Vec<String> = predictions.spread.iter().map(|s| s.to_string()).collect();
+            option(&mut args, "--spread", Some(spreads.join(",")));
+        }
+        option(&mut args, "--opt", self.opt.as_ref());
+        if predictions.relative {
+            args.push("--rel-sigma".to_string());
+        }
+        if predictions.requested {
+            args.push("--requested".to_string());
+        }
+        if !self.algorithms.is_empty() {
+            let specs: Vec<String> = self
+                .algorithms
+                .iter()
+                .flat_map(|alg| {
+                    if alg.params.is_empty() {
+                        vec![alg.key.clone()]
+                    } else {
+                        alg.params
+                            .iter()
+                            .map(|param| format!("{}:{}", alg.key, param))
+                            .collect()
+                    }
+                })
+                .collect();
+            option(&mut args, "--algs", Some(specs.join(",")));
+        }
+        if !self.learn.is_empty() {
+            let grid: Vec<String> = self.learn.iter().map(|lambda| lambda.to_string()).collect();
+            option(&mut args, "--learn", Some(grid.join(",")));
+        }
+        Ok(args)
+    }
+}
+
+fn option<T: ToString>(args: &mut Vec<String>, name: &str, value: Option<T>) {
+    if let Some(value) = value {
+        args.push(name.to_string());
+        args.push(value.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_to_command_line() {
+        let config: Config = toml::from_str(
+            r#"
+            experiment = "exp3"
+            output = "exp3.csv"
+
+            [instances]
+            count = 10
+            machines = 5
+            speeds = [2.0, 1.0]
+            lengths = "pareto:1.1"
+
+            [predictions]
+            relative = true
+            base_sigma = 1.1
+
+            [[algorithms]]
+            key = "pts"
+            params = [0.1, 0.5]
+
+            [[algorithms]]
+            key = "wdeq"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.args().unwrap(),
+            vec![
+                "exp3",
+                "-n",
+                "10",
+                "-m",
+                "5",
+                "--speeds",
+                "2,1",
+                "--length-dist",
+                "pareto:1.1",
+                "--base-sigma",
+                "1.1",
+                "--rel-sigma",
+                "--algs",
+                "pts:0.1,pts:0.5,wdeq",
+            ]
+        );
+    }
+
+    #[test]
+    fn passes_the_trace_file_positionally() {
+        let config: Config = serde_json::from_str(
+            r#"{"experiment": "trace", "instances": {"file": "kth.swf", "count": 2}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.args().unwrap(), vec!["trace", "kth.swf", "-n", "2"]);
+
+        let config: Config = toml::from_str("experiment = \"run\"").unwrap();
+        assert!(config.args().is_err());
+        assert!(toml::from_str::<Config>("experiment = \"exp1\"\nalgs = \"rr\"").is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! Problems and predictions on disk, so that experiments can be rerun on the same data. Files
+//! ending in `.json` hold a list of [`StoredProblem`]s; all other files are CSV with one row
+//! per job and prediction (see [`JobRow`]).
+
+use std::{fs::File, path::Path};
+
+use anyhow::{bail, Context, Result};
+use csv::{Reader, Writer};
+use serde::{Deserialize, Serialize};
+
+use crate::{instance::ProblemInstance, prediction::InstancePrediction};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoredProblem {
+    #[serde(flatten)]
+    pub problem: ProblemInstance,
+    #[serde(default)]
+    pub predictions: Vec<StoredPrediction>,
+}
+
+/// Predicted lengths together with the noise parameter they were drawn with.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoredPrediction {
+    pub sigma: f64,
+    pub lengths: InstancePrediction,
+}
+
+/// A job of a problem in the CSV format. Problems without predictions have one row per job
+/// with empty prediction columns; otherwise every prediction repeats the jobs. The format only
+/// holds sequential jobs on identical machines.
+#[derive(Debug, Serialize, Deserialize)]
+struct JobRow {
+    instance: usize,
+    m: usize,
+    job: usize,
+    length: f64,
+    weight: f64,
+    release: f64,
+    prediction: Option<usize>,
+    sigma: Option<f64>,
+    predicted: Option<f64>,
+}
+
+pub fn write(path: &Path, problems: &[StoredProblem]) -> Result<()> {
+    if !is_json(path) {
+        let special = problems
+            .iter()
+            .position(|stored| !stored.problem.is_identical() || stored.problem.is_malleable());
+        if let Some(i) = special {
+            bail!(
+                "{}: problem {} has machine speeds, unrelated machines or speed-up curves, \
+                 which only JSON files hold",
+                path.display(),
+                i
+            );
+        }
+    }
+    let file = File::create(path).with_context(|| format!("cannot create {}", path.display()))?;
+    if is_json(path) {
+        serde_json::to_writer(file, problems)?;
+        return Ok(());
+    }
+    let mut wtr = Writer::from_writer(file);
+    for (i, stored) in problems.iter().enumerate() {
+        let problem = &stored.problem;
+        let row = |job: usize, prediction: Option<(usize, &StoredPrediction)>| JobRow {
+            instance: i,
+            m: problem.m,
+            job,
+            length: problem.instance[job],
+            weight: problem.weights[job],
+            release: problem.releases[job],
+            prediction: prediction.map(|(k, _)| k),
+            sigma: prediction.map(|(_, pred)| pred.sigma),
+            predicted: prediction.map(|(_, pred)| pred.lengths[job]),
+        };
+        if stored.predictions.is_empty() {
+            for job in 0..problem.instance.len() {
+                wtr.serialize(row(job, None))?;
+            }
+        }
+        for prediction in stored.predictions.iter().enumerate() {
+            for job in 0..problem.instance.len() {
+                wtr.serialize(row(job, Some(prediction)))?;
+            }
+        }
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn read(path: &Path) -> Result<Vec<StoredProblem>> {
+    let file = File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+    let problems: Vec<StoredProblem> = if is_json(path) {
+        serde_json::from_reader(file)
+            .with_context(|| format!("invalid problem file {}", path.display()))?
+    } else {
+        let rows: Vec<JobRow> = Reader::from_reader(file)
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .with_context(|| format!("invalid problem file {}", path.display()))?;
+        let mut problems: Vec<StoredProblem> = vec![];
+        for row in rows {
+            if row.instance == problems.len() {
+                problems.push(StoredProblem {
+                    problem: ProblemInstance {
+                        instance: vec![].into(),
+                        weights: vec![],
+                        releases: vec![],
+                        m: row.m,
+                        speeds: vec![],
+                        machine_lengths: vec![],
+                        speedups: vec![],
+                    },
+                    predictions: vec![],
+                });
+            } else if row.instance + 1 != problems.len() {
+                bail!(
+                    "{}: instances must be numbered consecutively",
+                    path.display()
+                );
+            }
+            let stored = problems.last_mut().unwrap();
+            if row.prediction.unwrap_or(0) == 0 {
+                if row.job != stored.problem.instance.len() {
+                    bail!("{}: jobs must be numbered consecutively", path.display());
+                }
+                push_job(&mut stored.problem, &row);
+            }
+            match (row.prediction, row.sigma, row.predicted) {
+                (None, None, None) => {}
+                (Some(k), Some(sigma), Some(predicted)) => {
+                    if k == stored.predictions.len() {
+                        stored.predictions.push(StoredPrediction {
+                            sigma,
+                            lengths: vec![].into(),
+                        });
+                    }
+                    match stored.predictions.get_mut(k) {
+                        Some(pred) => pred.lengths.jobs.push(predicted),
+                        None => bail!(
+                            "{}: predictions must be numbered consecutively",
+                            path.display()
+                        ),
+                    }
+                }
+                _ => bail!(
+                    "{}: incomplete prediction of instance {}",
+                    path.display(),
+                    row.instance
+                ),
+            }
+        }
+        problems
+    };
+    for (i, stored) in problems.iter().enumerate() {
+        stored
+            .problem
+            .check()
+            .with_context(|| format!("{}: invalid instance {}", path.display(), i))?;
+        let n = stored.problem.instance.len();
+        if stored
+            .predictions
+            .iter()
+            .any(|pred| pred.lengths.len() != n)
+        {
+            bail!(
+                "{}: instance {} has inconsistent job counts",
+                path.display(),
+                i
+            );
+        }
+    }
+    Ok(problems)
+}
+
+fn push_job(problem: &mut ProblemInstance, row: &JobRow) {
+    problem.instance.jobs.push(row.length);
+    problem.weights.push(row.weight);
+    problem.releases.push(row.release);
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension() == Some("json".as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::instance::SpeedUp;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("schedules-{}-{}", std::process::id(), name))
+    }
+
+    /// Problems on two machines, the first without predictions and the others with two each.
+    fn problems() -> Vec<StoredProblem> {
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..3)
+            .map(|i| {
+                let problem = ProblemInstance::random(5, 2, &mut rng);
+                let count = if i == 0 { 0 } else { 2 };
+                let predictions = (0..count)
+                    .map(|k| StoredPrediction {
+                        sigma: k as f64,
+                        lengths: problem.instance.jobs.iter().map(|p| p * 1.5).collect(),
+                    })
+                    .collect();
+                StoredProblem {
+                    problem,
+                    predictions,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn csv_and_json_roundtrip() {
+        let mut special = problems();
+        special[0].problem.speeds = vec![2.0, 0.5];
+        special[1].problem.machine_lengths = special[1]
+            .problem
+            .instance
+            .jobs
+            .iter()
+            .map(|&p| vec![p, 3.0 * p])
+            .collect();
+        special[2].problem.speedups = vec![SpeedUp::Amdahl { parallel: 0.9 }; 5];
+
+        for (name, problems) in &[("problems.csv", problems()), ("problems.json", special)] {
+            let path = temp_file(name);
+            write(&path, problems).unwrap();
+            let read = read(&path);
+            fs::remove_file(&path).unwrap();
+            assert_eq!(&read.unwrap(), problems);
+        }
+    }
+
+    #[test]
+    fn rejects_inconsistent_json() {
+        let mut broken = problems();
+        broken[0].problem.speeds = vec![1.0];
+        broken[1].problem.machine_lengths = vec![vec![1.0, 1.0]];
+        broken[2].problem.weights[0] = 0.0;
+        for (i, stored) in broken.into_iter().enumerate() {
+            let path = temp_file(&format!("broken-{}.json", i));
+            fs::write(&path, serde_json::to_string(&[stored]).unwrap()).unwrap();
+            let read = read(&path);
+            fs::remove_file(&path).unwrap();
+            assert!(read.is_err(), "problem {} was accepted", i);
+        }
+    }
+}
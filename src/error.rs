@@ -1,3 +1,7 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+
 use crate::{
     algorithms::spt,
     instance::Instance,
@@ -63,3 +67,65 @@ impl ErrorMeasure<PermutationPrediction> for InversionError {
         error
     }
 }
+
+/// An error measure that experiments can report for each prediction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Measure {
+    Simple,
+    MaxMin,
+    Inversion,
+}
+
+impl Measure {
+    /// The error of predicted lengths; the inversion error is that of the order they induce.
+    pub fn compute(self, instance: &Instance, pred: &InstancePrediction) -> f64 {
+        match self {
+            Measure::Simple => SimpleError::compute(instance, pred),
+            Measure::MaxMin => MaxMinError::compute(instance, pred),
+            Measure::Inversion => {
+                InversionError::compute(instance, &PermutationPrediction::induced_by(pred))
+            }
+        }
+    }
+}
+
+impl FromStr for Measure {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "simple" => Ok(Measure::Simple),
+            "maxmin" => Ok(Measure::MaxMin),
+            "inversion" => Ok(Measure::Inversion),
+            _ => bail!(
+                "unknown error measure '{}', expected 'simple', 'maxmin' or 'inversion'",
+                s
+            ),
+        }
+    }
+}
+
+/// The selected error measures of one prediction, `None` for those not selected.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Errors {
+    pub simple: Option<f64>,
+    pub maxmin: Option<f64>,
+    pub inversion: Option<f64>,
+}
+
+impl Errors {
+    pub fn compute(measures: &[Measure], instance: &Instance, pred: &InstancePrediction) -> Self {
+        let get = |measure: Measure| {
+            if measures.contains(&measure) {
+                Some(measure.compute(instance, pred))
+            } else {
+                None
+            }
+        };
+        Errors {
+            simple: get(Measure::Simple),
+            maxmin: get(Measure::MaxMin),
+            inversion: get(Measure::Inversion),
+        }
+    }
+}
@@ -1,17 +1,18 @@
-use std::{iter::FromIterator, ops::Index};
+use std::{iter::FromIterator, ops::Index, str::FromStr};
 
-use rand::{distributions::Distribution, prelude::SliceRandom};
-use rand_distr::{Pareto,Exp1,Weibull,Zipf};
+use anyhow::{anyhow, bail, Result};
+use rand::{distributions::Distribution, Rng};
+use rand_distr::{Exp, LogNormal, Normal, Pareto, Weibull, Zipf};
+use serde::{Deserialize, Serialize};
 
 use crate::Gen;
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Instance {
     pub jobs: Vec<f64>,
 }
 
-
-
 impl Instance {
     pub fn len(&self) -> usize {
         self.jobs.len()
@@ -49,53 +50,471 @@ impl<'a> IntoIterator for &'a Instance {
     }
 }
 
-pub struct InstanceGenParams {
-    pub length: usize,
-    pub alpha: f64,
+/// A scheduling problem: job lengths, weights and release dates on `m` identical machines, on
+/// uniformly related machines with the given speeds, or on unrelated machines with a length for
+/// every job and machine. Jobs are sequential unless they have speed-up curves.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProblemInstance {
+    #[serde(rename = "lengths")]
+    pub instance: Instance,
+    pub weights: Vec<f64>,
+    pub releases: Vec<f64>,
+    pub m: usize,
+    /// Speeds of the `m` machines; empty for identical machines of speed 1.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub speeds: Vec<f64>,
+    /// Length of every job on every machine, `machine_lengths[j][i]` for job `j` on machine
+    /// `i`; empty unless the machines are unrelated. The length in `instance` is then the
+    /// length on the job's best machine.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub machine_lengths: Vec<Vec<f64>>,
+    /// Speed-up curve of every job on identical machines; empty for sequential jobs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub speedups: Vec<SpeedUp>,
 }
 
-impl Gen<InstanceGenParams> for Instance {
-    fn generate(params: &InstanceGenParams) -> Instance {
-        let mut rng = rand::thread_rng();
-        let dist = Pareto::new(1.0, params.alpha).unwrap();
-        //let dist = Weibull::new(2.0, 0.5).unwrap();
-        //let dist = Zipf::new(20000, 1.1).unwrap();
+impl ProblemInstance {
+    /// Unweighted single-machine problem where all jobs are released at time 0.
+    pub fn single_machine(instance: Instance) -> Self {
+        let n = instance.len();
+        ProblemInstance {
+            instance,
+            weights: vec![1.0; n],
+            releases: vec![0.0; n],
+            m: 1,
+            speeds: vec![],
+            machine_lengths: vec![],
+            speedups: vec![],
+        }
+    }
+
+    /// The speeds of the machines in non-increasing order.
+    pub fn machine_speeds(&self) -> Vec<f64> {
+        if self.speeds.is_empty() {
+            vec![1.0; self.m]
+        } else {
+            let mut speeds = self.speeds.clone();
+            speeds.sort_by(|a, b| b.partial_cmp(a).unwrap());
+            speeds
+        }
+    }
+
+    pub fn total_speed(&self) -> f64 {
+        if self.speeds.is_empty() {
+            self.m as f64
+        } else {
+            self.speeds.iter().sum()
+        }
+    }
+
+    pub fn fastest_speed(&self) -> f64 {
+        if self.speeds.is_empty() {
+            1.0
+        } else {
+            self.speeds.iter().cloned().fold(0.0, f64::max)
+        }
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.speeds.is_empty() && self.machine_lengths.is_empty()
+    }
+
+    pub fn is_unrelated(&self) -> bool {
+        !self.machine_lengths.is_empty()
+    }
+
+    pub fn is_malleable(&self) -> bool {
+        !self.speedups.is_empty()
+    }
+
+    /// Speed-up curve of job `j`; sequential jobs are linear up to one machine.
+    pub fn speedup(&self, j: usize) -> SpeedUp {
+        self.speedups
+            .get(j)
+            .copied()
+            .unwrap_or(SpeedUp::Linear { bound: 1.0 })
+    }
 
-        let mut jobs: Vec<f64> = dist
-            .sample_iter(&mut rng)
-            .take(params.length)
-            .map(|j: f64| j)
-            .collect();
+    /// Largest rate at which job `j` can be processed: the fastest speed, or the rate on all
+    /// machines for malleable jobs.
+    pub fn max_rate(&self, j: usize) -> f64 {
+        if self.is_malleable() {
+            self.speedups[j].rate(self.m as f64)
+        } else {
+            self.fastest_speed()
+        }
+    }
+
+    /// Speed of job `j` on machine `i` (machines in their given order) relative to its length
+    /// in `instance`, which is at most 1 on unrelated machines.
+    pub fn job_speed(&self, j: usize, i: usize) -> f64 {
+        if self.is_unrelated() {
+            self.instance[j] / self.machine_lengths[j][i]
+        } else if self.speeds.is_empty() {
+            1.0
+        } else {
+            self.speeds[i]
+        }
+    }
 
-        jobs.shuffle(&mut rng);
+    /// Whether this is the setting of [`ProblemInstance::single_machine`], for which the
+    /// original single-machine algorithms apply.
+    pub fn is_single_machine(&self) -> bool {
+        self.m == 1
+            && self.is_identical()
+            && self.weights.iter().all(|&w| w == 1.0)
+            && self.releases.iter().all(|&r| r == 0.0)
+    }
 
-        jobs.into()
+    /// Checks that the parts of the problem fit together, as problems read from files need not:
+    /// a weight, a release date and, if given, a speed-up curve and machine lengths for every
+    /// job, a speed for every machine, positive lengths, weights and speeds and non-negative
+    /// release dates.
+    pub fn check(&self) -> Result<()> {
+        let n = self.instance.len();
+        if self.m == 0 {
+            bail!("there must be at least one machine");
+        }
+        if self.weights.len() != n || self.releases.len() != n {
+            bail!(
+                "{} jobs, but {} weights and {} release dates",
+                n,
+                self.weights.len(),
+                self.releases.len()
+            );
+        }
+        if !self.speeds.is_empty() && self.speeds.len() != self.m {
+            bail!("{} machines, but {} speeds", self.m, self.speeds.len());
+        }
+        if !self.machine_lengths.is_empty()
+            && (self.machine_lengths.len() != n
+                || self.machine_lengths.iter().any(|row| row.len() != self.m))
+        {
+            bail!("the machine lengths must have a row of {} per job", self.m);
+        }
+        if !self.speedups.is_empty() && self.speedups.len() != n {
+            bail!("{} jobs, but {} speed-up curves", n, self.speedups.len());
+        }
+        let positive = |values: &[f64]| values.iter().all(|&v| v > 0.0 && v.is_finite());
+        if !positive(&self.instance.jobs)
+            || !positive(&self.weights)
+            || !positive(&self.speeds)
+            || !self.machine_lengths.iter().all(|row| positive(row))
+        {
+            bail!("lengths, weights and speeds must be positive");
+        }
+        if !self.releases.iter().all(|&r| r >= 0.0 && r.is_finite()) {
+            bail!("release dates must be non-negative");
+        }
+        Ok(())
     }
 }
 
-pub fn sample_floats(alpha: f64, num: usize) -> Vec<f64> {
-    let mut rng = rand::thread_rng();
-        let dist = Pareto::new(1.0, alpha).unwrap();
-        //let dist = Weibull::new(2.0, 0.5).unwrap();
-        //let dist = Zipf::new(20000, 1.1).unwrap();
+#[cfg(test)]
+impl ProblemInstance {
+    /// Random problem of `n` jobs on `m` identical machines with Pareto lengths and weights and
+    /// integer release dates, like the default problems of exp3.
+    pub fn random<R: Rng>(n: usize, m: usize, rng: &mut R) -> Self {
+        let lengths = JobDist::Pareto { alpha: 1.1 }.sample_n(n, rng);
+        ProblemInstance {
+            weights: JobDist::Pareto { alpha: 2.0 }.sample_n(n, rng),
+            releases: JobDist::Pareto { alpha: 2.0 }.sample_integers(n, rng),
+            m,
+            ..ProblemInstance::single_machine(lengths.into())
+        }
+    }
+}
 
-        let mut jobs: Vec<f64> = dist
-            .sample_iter(&mut rng)
-            .take(num)
-            .map(|j: f64| j)
-            .collect();
+/// Speed-up curve of a malleable job: the rate at which it is processed on `x` machines, where
+/// a fractional `x` is a share of the machines' time. All curves are linear up to one machine
+/// and concave, so that no job processes more than one unit of work per unit of machine time.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "curve", rename_all = "lowercase")]
+pub enum SpeedUp {
+    /// Linear up to `bound` machines, constant beyond.
+    Linear { bound: f64 },
+    /// Amdahl's law with parallel fraction `parallel`: `1 / (1 - f + f / x)` beyond one machine.
+    Amdahl { parallel: f64 },
+    /// `x^exponent` beyond one machine.
+    Power { exponent: f64 },
+}
 
-        jobs.shuffle(&mut rng);
+impl SpeedUp {
+    pub fn rate(&self, machines: f64) -> f64 {
+        if machines <= 1.0 {
+            return machines;
+        }
+        match *self {
+            SpeedUp::Linear { bound } => machines.min(bound),
+            SpeedUp::Amdahl { parallel } => 1.0 / (1.0 - parallel + parallel / machines),
+            SpeedUp::Power { exponent } => machines.powf(exponent),
+        }
+    }
 
-        jobs
+    /// The fewest machines that reach `rate`, infinite if no number of machines does.
+    pub fn machines(&self, rate: f64) -> f64 {
+        if rate <= 1.0 {
+            return rate;
+        }
+        match *self {
+            SpeedUp::Linear { bound } if rate <= bound => rate,
+            SpeedUp::Linear { .. } => f64::INFINITY,
+            SpeedUp::Amdahl { parallel } if 1.0 / rate > 1.0 - parallel => {
+                parallel / (1.0 / rate - (1.0 - parallel))
+            }
+            SpeedUp::Amdahl { .. } => f64::INFINITY,
+            SpeedUp::Power { exponent } => rate.powf(1.0 / exponent),
+        }
+    }
 }
 
-pub fn sample_integers(alpha: f64, num: usize) -> Vec<usize> {
-    sample_floats(alpha, num).into_iter().map(|f| f.round() as usize).collect()
+/// Distribution of speed-up curves, given on the command line as `curve:low[,high]` with the
+/// parameter of the curve drawn uniformly from `[low, high]`, or fixed to `low`:
+///
+/// - `linear:bound` with a parallelism bound of at least 1,
+/// - `amdahl:parallel` with a parallel fraction in `[0, 1]`,
+/// - `power:exponent` with an exponent in `(0, 1]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpeedUpDist {
+    curve: SpeedUp,
+    low: f64,
+    high: f64,
 }
 
+impl SpeedUpDist {
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> SpeedUp {
+        let param = if self.low < self.high {
+            rng.gen_range(self.low..=self.high)
+        } else {
+            self.low
+        };
+        match self.curve {
+            SpeedUp::Linear { .. } => SpeedUp::Linear { bound: param },
+            SpeedUp::Amdahl { .. } => SpeedUp::Amdahl { parallel: param },
+            SpeedUp::Power { .. } => SpeedUp::Power { exponent: param },
+        }
+    }
+
+    pub fn sample_n<R: Rng>(&self, num: usize, rng: &mut R) -> Vec<SpeedUp> {
+        (0..num).map(|_| self.sample(rng)).collect()
+    }
+}
+
+impl FromStr for SpeedUpDist {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, params) = s.split_once(':').unwrap_or((s, ""));
+        let params: Vec<f64> = params
+            .split(',')
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| p.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| anyhow!("invalid parameter in speed-up curve '{}'", s))?;
+        let (low, high) = match params[..] {
+            [param] => (param, param),
+            [low, high] if low <= high => (low, high),
+            _ => bail!("speed-up curve '{}' takes a parameter or a range", s),
+        };
+        let (curve, valid) = match name.trim() {
+            "linear" => (SpeedUp::Linear { bound: low }, low >= 1.0),
+            "amdahl" => (SpeedUp::Amdahl { parallel: low }, low >= 0.0 && high <= 1.0),
+            "power" => (SpeedUp::Power { exponent: low }, low > 0.0 && high <= 1.0),
+            _ => bail!(
+                "unknown speed-up curve '{}', expected one of linear, amdahl, power",
+                name
+            ),
+        };
+        if !valid {
+            bail!("invalid parameters for speed-up curve '{}'", s);
+        }
+        Ok(SpeedUpDist { curve, low, high })
+    }
+}
+
+pub struct InstanceGenParams {
+    pub length: usize,
+    pub dist: JobDist,
+}
+
+impl Gen<InstanceGenParams> for Instance {
+    fn generate<R: Rng>(params: &InstanceGenParams, rng: &mut R) -> Instance {
+        params.dist.sample_n(params.length, rng).into()
+    }
+}
+
+/// Distribution of job lengths, weights or release dates. Given on the command line as
+/// `name:param,...`:
+///
+/// - `pareto:alpha` with scale 1,
+/// - `weibull:scale,shape`,
+/// - `exponential:lambda`,
+/// - `uniform:low,high`,
+/// - `lognormal:mu,sigma`,
+/// - `bimodal:short,long,p`, normal around `short` or, with probability `p`, around `long`,
+///   with a standard deviation of a tenth of the mean,
+/// - `zipf:n,s` on the integers `1..=n` for a positive integer `n`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobDist {
+    Pareto { alpha: f64 },
+    Weibull { scale: f64, shape: f64 },
+    Exponential { lambda: f64 },
+    Uniform { low: f64, high: f64 },
+    LogNormal { mu: f64, sigma: f64 },
+    Bimodal { short: f64, long: f64, p: f64 },
+    Zipf { n: u64, s: f64 },
+}
+
+impl JobDist {
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        match *self {
+            JobDist::Pareto { alpha } => Pareto::new(1.0, alpha).unwrap().sample(rng),
+            JobDist::Weibull { scale, shape } => Weibull::new(scale, shape).unwrap().sample(rng),
+            JobDist::Exponential { lambda } => Exp::new(lambda).unwrap().sample(rng),
+            JobDist::Uniform { low, high } => rng.gen_range(low..high),
+            JobDist::LogNormal { mu, sigma } => LogNormal::new(mu, sigma).unwrap().sample(rng),
+            JobDist::Bimodal { short, long, p } => {
+                let mean = if rng.gen_bool(p) { long } else { short };
+                let dist = Normal::new(mean, mean / 10.0).unwrap();
+                let mut x = dist.sample(rng);
+                while x <= 0.0 {
+                    x = dist.sample(rng);
+                }
+                x
+            }
+            JobDist::Zipf { n, s } => Zipf::new(n, s).unwrap().sample(rng),
+        }
+    }
+
+    /// `num` independent samples.
+    pub fn sample_n<R: Rng>(&self, num: usize, rng: &mut R) -> Vec<f64> {
+        (0..num).map(|_| self.sample(rng)).collect()
+    }
 
-pub fn analyse_instances(instances: &Vec<Instance>) {
+    /// `num` independent samples rounded to integers, as used for release dates.
+    pub fn sample_integers<R: Rng>(&self, num: usize, rng: &mut R) -> Vec<f64> {
+        self.sample_n(num, rng)
+            .into_iter()
+            .map(f64::round)
+            .collect()
+    }
+
+    /// Rejects parameters for which the distribution is undefined or not positive.
+    fn check(&self) -> Result<()> {
+        let valid = match *self {
+            JobDist::Pareto { alpha } => Pareto::new(1.0, alpha).is_ok(),
+            JobDist::Weibull { scale, shape } => Weibull::new(scale, shape).is_ok(),
+            JobDist::Exponential { lambda } => lambda > 0.0 && Exp::new(lambda).is_ok(),
+            JobDist::Uniform { low, high } => low >= 0.0 && low < high,
+            JobDist::LogNormal { mu, sigma } => LogNormal::new(mu, sigma).is_ok(),
+            JobDist::Bimodal { short, long, p } => {
+                short > 0.0 && long > 0.0 && (0.0..=1.0).contains(&p)
+            }
+            JobDist::Zipf { n, s } => Zipf::new(n, s).is_ok(),
+        };
+        if !valid {
+            bail!("invalid parameters for {:?}", self);
+        }
+        Ok(())
+    }
+
+    /// Parses a distribution of job lengths, weights or machine factors, which, unlike release
+    /// dates, must be strictly positive: a uniform distribution needs `low > 0`. The other
+    /// families only take positive values once their parameters are valid.
+    pub fn positive(s: &str) -> Result<Self> {
+        let dist: JobDist = s.parse()?;
+        if let JobDist::Uniform { low, .. } = dist {
+            if low <= 0.0 {
+                bail!("the values must be positive, but '{}' can draw {}", s, low);
+            }
+        }
+        Ok(dist)
+    }
+}
+
+impl FromStr for JobDist {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, params) = s.split_once(':').unwrap_or((s, ""));
+        let params: Vec<f64> = params
+            .split(',')
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| p.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| anyhow!("invalid parameter in distribution '{}'", s))?;
+        let expect = |count: usize| {
+            if params.len() == count {
+                Ok(())
+            } else {
+                Err(anyhow!(
+                    "distribution '{}' takes {} parameter(s), got {}",
+                    name,
+                    count,
+                    params.len()
+                ))
+            }
+        };
+        let dist = match name.trim() {
+            "pareto" => {
+                expect(1)?;
+                JobDist::Pareto { alpha: params[0] }
+            }
+            "weibull" => {
+                expect(2)?;
+                JobDist::Weibull {
+                    scale: params[0],
+                    shape: params[1],
+                }
+            }
+            "exponential" => {
+                expect(1)?;
+                JobDist::Exponential { lambda: params[0] }
+            }
+            "uniform" => {
+                expect(2)?;
+                JobDist::Uniform {
+                    low: params[0],
+                    high: params[1],
+                }
+            }
+            "lognormal" => {
+                expect(2)?;
+                JobDist::LogNormal {
+                    mu: params[0],
+                    sigma: params[1],
+                }
+            }
+            "bimodal" => {
+                expect(3)?;
+                JobDist::Bimodal {
+                    short: params[0],
+                    long: params[1],
+                    p: params[2],
+                }
+            }
+            "zipf" => {
+                expect(2)?;
+                if params[0] < 1.0 || params[0].fract() != 0.0 {
+                    bail!("the support size of '{}' must be a positive integer", s);
+                }
+                JobDist::Zipf {
+                    n: params[0] as u64,
+                    s: params[1],
+                }
+            }
+            _ => bail!(
+                "unknown distribution '{}', expected one of pareto, weibull, exponential, \
+                 uniform, lognormal, bimodal, zipf",
+                name
+            ),
+        };
+        dist.check()?;
+        Ok(dist)
+    }
+}
+
+pub fn analyse_instances(instances: &[Instance]) {
     let flat: Vec<f64> = instances
         .iter()
         .flat_map(|instance| instance.jobs.clone())
@@ -112,7 +531,7 @@ pub fn analyse_instances(instances: &Vec<Instance>) {
 }
 
 fn mean(data: &[f64]) -> Option<f64> {
-    let sum = data.iter().sum::<f64>() as f64;
+    let sum = data.iter().sum::<f64>();
     let count = data.len();
 
     match count {
@@ -127,7 +546,7 @@ fn std_deviation(data: &[f64]) -> Option<f64> {
             let variance = data
                 .iter()
                 .map(|value| {
-                    let diff = data_mean - (*value as f64);
+                    let diff = data_mean - *value;
 
                     diff * diff
                 })
@@ -139,3 +558,66 @@ fn std_deviation(data: &[f64]) -> Option<f64> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_distributions() {
+        let cases = [
+            ("pareto:1.1", JobDist::Pareto { alpha: 1.1 }),
+            (
+                "weibull:10, 0.5",
+                JobDist::Weibull {
+                    scale: 10.0,
+                    shape: 0.5,
+                },
+            ),
+            ("exponential:0.01", JobDist::Exponential { lambda: 0.01 }),
+            (
+                "uniform:0,1",
+                JobDist::Uniform {
+                    low: 0.0,
+                    high: 1.0,
+                },
+            ),
+            (
+                "bimodal:1,10,0.2",
+                JobDist::Bimodal {
+                    short: 1.0,
+                    long: 10.0,
+                    p: 0.2,
+                },
+            ),
+            ("zipf:100,1.5", JobDist::Zipf { n: 100, s: 1.5 }),
+        ];
+        for (s, dist) in &cases {
+            assert_eq!(s.parse::<JobDist>().unwrap(), *dist, "{}", s);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_distributions() {
+        for s in &[
+            "pareto",
+            "pareto:x",
+            "pareto:1,2",
+            "normal:1",
+            "uniform:2,1",
+            "bimodal:1,10,2",
+            "zipf:2.5,1",
+            "zipf:0,1",
+        ] {
+            assert!(s.parse::<JobDist>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn positive_distributions_exclude_zero() {
+        assert!(JobDist::positive("uniform:0,1").is_err());
+        assert!(JobDist::positive("uniform:-1,1").is_err());
+        assert!(JobDist::positive("uniform:0.5,1").is_ok());
+        assert!(JobDist::positive("pareto:1.1").is_ok());
+    }
+}
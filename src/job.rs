@@ -1,7 +1,10 @@
+use crate::{alg_identical::wdeq_shares, schedule::Schedule};
+
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Job {
     pub length: f64,
     pub pred: f64,
+    pub weight: f64,
     pub completed: bool,
     pub id: usize,
 }
@@ -12,6 +15,7 @@ impl Job {
             id,
             length,
             pred,
+            weight: 1.0,
             completed: false,
         }
     }
@@ -19,21 +23,31 @@ impl Job {
 
 pub struct Environment {
     pub time: f64,
-    pub obj: f64,
     pub n: usize,
+    pub machines: usize,
     pub jobs: Vec<Job>,
+    pub schedule: Schedule,
 }
 
 impl Environment {
-    pub fn new(jobs: Vec<Job>) -> Self {
+    /// Environment for `jobs` on `machines` identical machines from `time` on, appending to
+    /// `schedule`.
+    pub fn new(jobs: Vec<Job>, machines: usize, time: f64, schedule: Schedule) -> Self {
         Environment {
-            time: 0.0,
-            obj: 0.0,
+            time,
             n: jobs.len(),
+            machines,
+            schedule,
             jobs,
         }
     }
 
+    /// Adds a newly released job.
+    pub fn admit(&mut self, job: Job) {
+        self.jobs.push(job);
+        self.n += 1;
+    }
+
     pub fn nk(&self) -> usize {
         self.jobs.len()
     }
@@ -42,12 +56,13 @@ impl Environment {
         if let Some(job) = self.jobs.get_mut(job_idx) {
             job.length -= amount;
             job.pred = (job.pred - amount).max(0.0);
-            if job.length < 0.0 {
+            if job.length < -1e-9 * amount.max(1.0) {
                 panic!("job length < 0! {}", job.length)
             }
-            if job.length == 0.0 {
+            if job.length <= 0.0 {
+                job.length = 0.0;
                 job.completed = true;
-                self.obj += self.time;
+                self.schedule.complete(job.id, self.time);
                 return true;
             }
             false
@@ -60,7 +75,7 @@ impl Environment {
         if let Some(job) = self.jobs.get_mut(job_idx) {
             job.completed = true;
             job.length = 0.0;
-            self.obj += self.time;
+            self.schedule.complete(job.id, self.time);
         }
     }
 
@@ -68,7 +83,81 @@ impl Environment {
         self.jobs.retain(|j| !j.completed)
     }
 
-    pub fn run_for(&mut self, time: f64) {
+    /// Advances time by `time` while processing the jobs at the given `(index, rate)` pairs.
+    /// Only the schedule is updated; lengths are reduced via [`Environment::process`].
+    pub fn run_for<I: IntoIterator<Item = (usize, f64)>>(&mut self, time: f64, rates: I) {
+        let jobs = &self.jobs;
+        self.schedule.process(
+            self.time,
+            self.time + time,
+            rates.into_iter().map(|(idx, rate)| (jobs[idx].id, rate)),
+        );
         self.time += time;
     }
+
+    /// Shares the machines among the `(index, amount, weight)` targets in proportion to their
+    /// weights, at most one machine per job, until every job received its amount or time
+    /// `until` is reached. On a single machine this is weighted round robin.
+    pub fn share(&mut self, targets: &[(usize, f64, f64)], until: f64) {
+        let mut left: Vec<(usize, f64, f64)> =
+            targets.iter().copied().filter(|t| t.1 > 0.0).collect();
+        while !left.is_empty() && self.time < until {
+            let weights: Vec<f64> = left.iter().map(|t| t.2).collect();
+            let mut running: Vec<(usize, f64, f64)> = left
+                .iter()
+                .zip(wdeq_shares(&weights, self.machines))
+                .map(|(&(idx, amount, _), rate)| (idx, amount, rate))
+                .collect();
+            self.advance(&mut running, until);
+            for (target, run) in left.iter_mut().zip(&running) {
+                target.1 = run.1;
+            }
+            left.retain(|t| t.1 > 0.0);
+        }
+    }
+
+    /// Runs the `(index, amount)` targets at rate one in the given order, each on the first
+    /// machine that becomes free.
+    pub fn run_in_order(&mut self, targets: &[(usize, f64)]) {
+        let mut queue = targets.iter().filter(|t| t.1 > 0.0);
+        let mut running: Vec<(usize, f64, f64)> = vec![];
+        loop {
+            while running.len() < self.machines {
+                match queue.next() {
+                    Some(&(idx, amount)) => running.push((idx, amount, 1.0)),
+                    None => break,
+                }
+            }
+            if running.is_empty() {
+                break;
+            }
+            self.advance(&mut running, f64::INFINITY);
+            running.retain(|t| t.1 > 0.0);
+        }
+    }
+
+    /// Processes the `(index, amount, rate)` jobs until the first of them has received its
+    /// amount, but not beyond time `until`. Amounts are reduced by the processing, to exactly
+    /// zero once reached, and jobs whose length is used up complete.
+    fn advance(&mut self, running: &mut [(usize, f64, f64)], until: f64) {
+        let dt = running
+            .iter()
+            .map(|&(_, amount, rate)| amount / rate)
+            .fold(until - self.time, f64::min);
+        self.run_for(dt, running.iter().map(|&(idx, _, rate)| (idx, rate)));
+        for target in running.iter_mut() {
+            let (idx, amount, rate) = *target;
+            let processed = if amount <= rate * dt * (1.0 + 1e-9) {
+                amount
+            } else {
+                rate * dt
+            };
+            target.1 = amount - processed;
+            if self.jobs[idx].length - processed <= 1e-9 * self.jobs[idx].length.max(1.0) {
+                self.complete(idx);
+            } else {
+                self.process(idx, processed);
+            }
+        }
+    }
 }
@@ -0,0 +1,64 @@
+//! Choosing the robustification parameter across repeated instances, as in exp2.
+
+/// Follow the leader over a grid of robustification parameters: every round uses the parameter
+/// with the smallest mean empirical competitive ratio on the rounds before, where the ratios
+/// reflect how good the predictions turned out to be. The first round uses the most robust
+/// parameter of the grid.
+pub struct LambdaLearner {
+    grid: Vec<f64>,
+    /// Sum of the empirical competitive ratios of every parameter over the observed rounds.
+    ratios: Vec<f64>,
+}
+
+impl LambdaLearner {
+    pub fn new(grid: Vec<f64>) -> Self {
+        assert!(!grid.is_empty(), "the lambda grid must not be empty");
+        LambdaLearner {
+            ratios: vec![0.0; grid.len()],
+            grid,
+        }
+    }
+
+    pub fn grid(&self) -> &[f64] {
+        &self.grid
+    }
+
+    /// Index into the grid of the parameter for the next round.
+    pub fn choice(&self) -> usize {
+        (0..self.grid.len())
+            .min_by(|&a, &b| {
+                self.ratios[a]
+                    .partial_cmp(&self.ratios[b])
+                    .unwrap()
+                    .then(self.grid[b].partial_cmp(&self.grid[a]).unwrap())
+            })
+            .unwrap()
+    }
+
+    /// Records a finished round, given the empirical competitive ratio every parameter of the
+    /// grid would have achieved on it.
+    pub fn observe(&mut self, ratios: &[f64]) {
+        assert_eq!(ratios.len(), self.grid.len());
+        for (total, ratio) in self.ratios.iter_mut().zip(ratios) {
+            *total += ratio;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_the_leader() {
+        let mut learner = LambdaLearner::new(vec![0.0, 0.5, 1.0]);
+        assert_eq!(learner.choice(), 2);
+        learner.observe(&[1.25, 1.5, 1.75]);
+        assert_eq!(learner.choice(), 0);
+        learner.observe(&[2.0, 1.25, 1.5]);
+        assert_eq!(learner.choice(), 1);
+        // ties go to the more robust parameter
+        learner.observe(&[1.5, 1.75, 1.25]);
+        assert_eq!(learner.choice(), 2);
+    }
+}
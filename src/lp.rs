@@ -0,0 +1,246 @@
+/// Numerical tolerance of the simplex method.
+const EPS: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relation {
+    Le,
+    Eq,
+    Ge,
+}
+
+/// A linear program `min c^T x` subject to linear constraints and `x >= 0`, solved with the
+/// two-phase simplex method using Bland's rule. Meant for the small programs of the exact
+/// solver, so the tableau is dense.
+#[derive(Clone, Debug)]
+pub struct LinearProgram {
+    n_vars: usize,
+    objective: Vec<f64>,
+    constraints: Vec<Constraint>,
+}
+
+/// Sparse coefficients, relation and right hand side.
+type Constraint = (Vec<(usize, f64)>, Relation, f64);
+
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub value: f64,
+    pub x: Vec<f64>,
+}
+
+impl LinearProgram {
+    pub fn new(n_vars: usize) -> Self {
+        LinearProgram {
+            n_vars,
+            objective: vec![0.0; n_vars],
+            constraints: vec![],
+        }
+    }
+
+    pub fn set_objective(&mut self, var: usize, coeff: f64) {
+        self.objective[var] = coeff;
+    }
+
+    pub fn add_constraint(&mut self, coeffs: Vec<(usize, f64)>, relation: Relation, rhs: f64) {
+        self.constraints.push((coeffs, relation, rhs));
+    }
+
+    /// Returns an optimal solution, or `None` if the program is infeasible.
+    pub fn solve(&self) -> Option<Solution> {
+        let rows = self.constraints.len();
+        let n_slack = self
+            .constraints
+            .iter()
+            .filter(|(_, rel, _)| *rel != Relation::Eq)
+            .count();
+        let n_art = self
+            .constraints
+            .iter()
+            .filter(|(_, rel, rhs)| match rel {
+                Relation::Le => *rhs < 0.0,
+                Relation::Eq => true,
+                Relation::Ge => *rhs >= 0.0,
+            })
+            .count();
+        let art_start = self.n_vars + n_slack;
+        let cols = art_start + n_art;
+
+        let mut tableau = vec![vec![0.0; cols + 1]; rows + 1];
+        let mut basis = vec![0; rows];
+        let mut slack = self.n_vars;
+        let mut art = art_start;
+        for (i, (coeffs, relation, rhs)) in self.constraints.iter().enumerate() {
+            // normalise to a non-negative right hand side
+            let sign = if *rhs < 0.0 { -1.0 } else { 1.0 };
+            for &(var, coeff) in coeffs {
+                tableau[i][var] += sign * coeff;
+            }
+            tableau[i][cols] = sign * rhs;
+            let slack_coeff = match relation {
+                Relation::Le => sign,
+                Relation::Ge => -sign,
+                Relation::Eq => 0.0,
+            };
+            if slack_coeff != 0.0 {
+                tableau[i][slack] = slack_coeff;
+                slack += 1;
+            }
+            if slack_coeff > 0.0 {
+                basis[i] = slack - 1;
+            } else {
+                tableau[i][art] = 1.0;
+                basis[i] = art;
+                art += 1;
+            }
+        }
+
+        // phase 1: minimise the sum of the artificial variables
+        let mut cost = vec![0.0; cols];
+        cost[art_start..].iter_mut().for_each(|c| *c = 1.0);
+        set_objective_row(&mut tableau, &basis, &cost);
+        run_simplex(&mut tableau, &mut basis, cols);
+        let scale = 1.0 + self.constraints.iter().map(|c| c.2.abs()).sum::<f64>();
+        if -tableau[rows][cols] > EPS * scale {
+            return None;
+        }
+        for i in 0..rows {
+            if basis[i] >= art_start {
+                if let Some(j) = (0..art_start).find(|&j| tableau[i][j].abs() > EPS) {
+                    pivot(&mut tableau, &mut basis, i, j);
+                }
+            }
+        }
+
+        // phase 2: the actual objective, artificial variables may not enter anymore
+        let mut cost = vec![0.0; cols];
+        cost[..self.n_vars].copy_from_slice(&self.objective);
+        set_objective_row(&mut tableau, &basis, &cost);
+        run_simplex(&mut tableau, &mut basis, art_start);
+
+        let mut x = vec![0.0; self.n_vars];
+        for (i, &b) in basis.iter().enumerate() {
+            if b < self.n_vars {
+                x[b] = tableau[i][cols];
+            }
+        }
+        Some(Solution {
+            value: -tableau[rows][cols],
+            x,
+        })
+    }
+}
+
+/// Writes the reduced costs of `cost` w.r.t. `basis` into the last row.
+fn set_objective_row(tableau: &mut [Vec<f64>], basis: &[usize], cost: &[f64]) {
+    let rows = basis.len();
+    let width = tableau[rows].len();
+    let mut obj = vec![0.0; width];
+    obj[..cost.len()].copy_from_slice(cost);
+    for (i, &b) in basis.iter().enumerate() {
+        if cost[b] != 0.0 {
+            for j in 0..width {
+                obj[j] -= cost[b] * tableau[i][j];
+            }
+        }
+    }
+    tableau[rows] = obj;
+}
+
+/// Pivots until no column below `allowed` has a negative reduced cost.
+fn run_simplex(tableau: &mut [Vec<f64>], basis: &mut [usize], allowed: usize) {
+    let rows = basis.len();
+    let rhs = tableau[rows].len() - 1;
+    while let Some(j) = (0..allowed).find(|&j| tableau[rows][j] < -EPS) {
+        let mut leave: Option<usize> = None;
+        for i in 0..rows {
+            if tableau[i][j] > EPS {
+                let better = match leave {
+                    None => true,
+                    Some(l) => {
+                        let ratio = tableau[i][rhs] / tableau[i][j];
+                        let best = tableau[l][rhs] / tableau[l][j];
+                        ratio < best - EPS || (ratio <= best + EPS && basis[i] < basis[l])
+                    }
+                };
+                if better {
+                    leave = Some(i);
+                }
+            }
+        }
+        let i = leave.expect("linear program is unbounded");
+        pivot(tableau, basis, i, j);
+    }
+}
+
+fn pivot(tableau: &mut [Vec<f64>], basis: &mut [usize], row: usize, col: usize) {
+    let p = tableau[row][col];
+    tableau[row].iter_mut().for_each(|v| *v /= p);
+    let pivot_row = tableau[row].clone();
+    for (i, r) in tableau.iter_mut().enumerate() {
+        if i != row && r[col].abs() > 0.0 {
+            let f = r[col];
+            for (v, pv) in r.iter_mut().zip(&pivot_row) {
+                *v -= f * pv;
+            }
+        }
+    }
+    basis[row] = col;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn solves_inequalities() {
+        // max x + y s.t. x + 2y <= 4, 3x + y <= 6
+        let mut lp = LinearProgram::new(2);
+        lp.set_objective(0, -1.0);
+        lp.set_objective(1, -1.0);
+        lp.add_constraint(vec![(0, 1.0), (1, 2.0)], Relation::Le, 4.0);
+        lp.add_constraint(vec![(0, 3.0), (1, 1.0)], Relation::Le, 6.0);
+        let solution = lp.solve().unwrap();
+        assert_close(solution.value, -2.8);
+        assert_close(solution.x[0], 1.6);
+        assert_close(solution.x[1], 1.2);
+    }
+
+    #[test]
+    fn solves_equalities_and_lower_bounds() {
+        // min x + y s.t. x + y >= 2, x - y = 1
+        let mut lp = LinearProgram::new(2);
+        lp.set_objective(0, 1.0);
+        lp.set_objective(1, 1.0);
+        lp.add_constraint(vec![(0, 1.0), (1, 1.0)], Relation::Ge, 2.0);
+        lp.add_constraint(vec![(0, 1.0), (1, -1.0)], Relation::Eq, 1.0);
+        let solution = lp.solve().unwrap();
+        assert_close(solution.value, 2.0);
+        assert_close(solution.x[0], 1.5);
+        assert_close(solution.x[1], 0.5);
+    }
+
+    #[test]
+    fn normalises_negative_right_hand_sides() {
+        // min x s.t. -x <= -3, -x >= -5
+        let mut lp = LinearProgram::new(1);
+        lp.set_objective(0, 1.0);
+        lp.add_constraint(vec![(0, -1.0)], Relation::Le, -3.0);
+        lp.add_constraint(vec![(0, -1.0)], Relation::Ge, -5.0);
+        assert_close(lp.solve().unwrap().value, 3.0);
+    }
+
+    #[test]
+    fn detects_infeasibility() {
+        let mut lp = LinearProgram::new(2);
+        lp.add_constraint(vec![(0, 1.0)], Relation::Le, 1.0);
+        lp.add_constraint(vec![(0, 1.0)], Relation::Ge, 2.0);
+        assert!(lp.solve().is_none());
+
+        let mut lp = LinearProgram::new(2);
+        lp.add_constraint(vec![(0, 1.0), (1, 1.0)], Relation::Eq, -1.0);
+        assert!(lp.solve().is_none());
+    }
+}
@@ -1,16 +1,34 @@
 use anyhow::Result;
+use rand::Rng;
 use sample::Cli;
 
+mod alg_identical;
+mod alg_malleable;
+mod alg_nonpreemptive;
+mod alg_unrelated;
 mod algorithms;
+mod bounds;
+mod config;
+mod dataset;
 mod error;
 mod instance;
 mod job;
+mod learning;
+mod lp;
+mod objective;
+mod opt;
 mod prediction;
 mod sample;
-mod alg_identical;
+mod schedule;
+mod scheduler;
+mod seed;
+mod simulator;
+mod stochastic;
+mod trace;
+mod validate;
 
 pub trait Gen<P> {
-    fn generate(params: &P) -> Self;
+    fn generate<R: Rng>(params: &P, rng: &mut R) -> Self;
 }
 
 #[paw::main]
@@ -0,0 +1,116 @@
+//! Objectives by which experiments compare schedules.
+
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+
+use crate::{bounds, instance::ProblemInstance, schedule::Schedule};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    /// Total weighted completion time `sum w_j C_j`.
+    Completion,
+    /// Total weighted flow time `sum w_j (C_j - r_j)`.
+    Flow,
+    /// Maximum flow time `max (C_j - r_j)`.
+    MaxFlow,
+    /// Total stretch `sum (C_j - r_j) / p_j`.
+    Stretch,
+}
+
+impl Objective {
+    pub fn value(self, problem: &ProblemInstance, schedule: &Schedule) -> f64 {
+        let flows = schedule
+            .completions
+            .iter()
+            .zip(&problem.releases)
+            .map(|(c, r)| c - r);
+        match self {
+            Objective::Completion => schedule.weighted_completion_time(&problem.weights),
+            Objective::Flow => flows.zip(&problem.weights).map(|(f, w)| w * f).sum(),
+            Objective::MaxFlow => flows.fold(0.0, f64::max),
+            Objective::Stretch => flows.zip(&problem.instance).map(|(f, p)| f / p).sum(),
+        }
+    }
+
+    /// Lower bound on the optimal value of `problem`.
+    pub fn lower_bound(self, problem: &ProblemInstance) -> f64 {
+        match self {
+            Objective::Completion => bounds::lower_bound(problem),
+            // the flow time differs from the completion time by the release dates in every
+            // schedule
+            Objective::Flow => {
+                bounds::lower_bound(problem)
+                    - problem
+                        .releases
+                        .iter()
+                        .zip(&problem.weights)
+                        .map(|(r, w)| w * r)
+                        .sum::<f64>()
+            }
+            Objective::MaxFlow => bounds::max_flow(problem),
+            // no job completes faster than its length at its largest rate
+            Objective::Stretch => (0..problem.instance.len())
+                .map(|j| 1.0 / problem.max_rate(j))
+                .sum(),
+        }
+    }
+}
+
+impl FromStr for Objective {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "completion" => Ok(Objective::Completion),
+            "flow" => Ok(Objective::Flow),
+            "max-flow" => Ok(Objective::MaxFlow),
+            "stretch" => Ok(Objective::Stretch),
+            _ => bail!(
+                "unknown objective '{}', expected 'completion', 'flow', 'max-flow' or 'stretch'",
+                s
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn values_and_bounds() {
+        let problem = ProblemInstance {
+            weights: vec![3.0, 1.0],
+            releases: vec![1.0, 0.0],
+            ..ProblemInstance::single_machine(vec![2.0, 4.0].into())
+        };
+        let mut schedule = Schedule::new(2, false);
+        schedule.complete(0, 3.0);
+        schedule.complete(1, 6.0);
+        assert_close(Objective::Completion.value(&problem, &schedule), 15.0);
+        assert_close(Objective::Flow.value(&problem, &schedule), 12.0);
+        assert_close(Objective::MaxFlow.value(&problem, &schedule), 6.0);
+        assert_close(Objective::Stretch.value(&problem, &schedule), 2.5);
+
+        assert_close(
+            Objective::Flow.lower_bound(&problem),
+            Objective::Completion.lower_bound(&problem) - 3.0,
+        );
+        assert_close(Objective::Stretch.lower_bound(&problem), 2.0);
+    }
+
+    #[test]
+    fn parses_objectives() {
+        assert_eq!(
+            " max-flow".parse::<Objective>().unwrap(),
+            Objective::MaxFlow
+        );
+        assert_eq!("stretch".parse::<Objective>().unwrap(), Objective::Stretch);
+        assert!("makespan".parse::<Objective>().is_err());
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{
+    alg_identical::pwspt,
+    instance::ProblemInstance,
+    lp::{LinearProgram, Relation},
+    prediction::Prediction,
+    schedule::Schedule,
+    scheduler::{Context, Scheduler},
+};
+
+/// Largest number of jobs the exact solver accepts; the number of event orders it enumerates
+/// grows like (2n-1)!!.
+pub const MAX_EXACT_JOBS: usize = 8;
+
+/// Relative length below which an interval between two events is empty.
+const EVENT_EPS: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Event {
+    /// Release of all jobs in a release group.
+    Release(usize),
+    Completion(usize),
+}
+
+/// Exact optimum of `P|r_j,pmtn|sum w_j C_j` for small instances.
+///
+/// Averaging the rates between two consecutive releases or completions keeps a schedule
+/// feasible, so some optimal schedule processes every job at a constant rate between events.
+/// For a fixed order of the events, the best such schedule is a linear program over the event
+/// times and the amount of processing of every job in every interval; McNaughton's wrap-around
+/// rule realises any rates of at most 1 summing to at most `m` on `m` machines. We enumerate
+/// the event orders by branch-and-bound and solve the linear program for each of them.
+pub fn optimum(problem: &ProblemInstance, ctx: &Context) -> Schedule {
+    let n = problem.instance.len();
+    assert!(
+        problem.is_identical() && !problem.is_malleable(),
+        "exact optimum is limited to sequential jobs on identical machines"
+    );
+    assert!(
+        n <= MAX_EXACT_JOBS,
+        "exact optimum is limited to {} jobs, got {}",
+        MAX_EXACT_JOBS,
+        n
+    );
+
+    let mut release_times: Vec<f64> = problem.releases.clone();
+    release_times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    release_times.dedup();
+    let groups: Vec<Vec<usize>> = release_times
+        .iter()
+        .map(|&r| (0..n).filter(|&j| problem.releases[j] == r).collect())
+        .collect();
+
+    let initial = pwspt(problem, ctx);
+    let mut search = Search {
+        problem,
+        release_times: &release_times,
+        groups: &groups,
+        events: vec![],
+        completed: vec![false; n],
+        best_value: initial.weighted_completion_time(&problem.weights),
+        best_events: None,
+    };
+    search.branch(0);
+
+    match search.best_events {
+        Some((events, x)) => build_schedule(problem, &release_times, &events, &x, ctx),
+        None => initial,
+    }
+}
+
+struct Search<'a> {
+    problem: &'a ProblemInstance,
+    release_times: &'a [f64],
+    groups: &'a [Vec<usize>],
+    events: Vec<Event>,
+    completed: Vec<bool>,
+    best_value: f64,
+    best_events: Option<(Vec<Event>, Vec<f64>)>,
+}
+
+impl Search<'_> {
+    /// Extends the current event order; `released` is the number of release groups in it.
+    fn branch(&mut self, released: usize) {
+        let problem = self.problem;
+        let (completed_cost, t_last) = self.prefix_bound();
+        let bound: f64 = completed_cost
+            + (0..problem.instance.len())
+                .filter(|&j| !self.completed[j])
+                .map(|j| problem.weights[j] * t_last.max(problem.releases[j] + problem.instance[j]))
+                .sum::<f64>();
+        if bound >= self.best_value * (1.0 - 1e-9) {
+            return;
+        }
+
+        if released == self.groups.len() && self.completed.iter().all(|&done| done) {
+            if let Some((value, x)) = solve_event_order(problem, self.release_times, &self.events) {
+                if value < self.best_value {
+                    self.best_value = value;
+                    self.best_events = Some((self.events.clone(), x));
+                }
+            }
+            return;
+        }
+
+        // complete an alive job, most promising first
+        let mut alive: Vec<usize> = self.groups[..released]
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&j| !self.completed[j])
+            .collect();
+        alive.sort_by(|&i, &j| {
+            (problem.weights[j] / problem.instance[j])
+                .partial_cmp(&(problem.weights[i] / problem.instance[i]))
+                .unwrap()
+        });
+        for j in alive {
+            self.events.push(Event::Completion(j));
+            self.completed[j] = true;
+            self.branch(released);
+            self.completed[j] = false;
+            self.events.pop();
+        }
+
+        if released < self.groups.len() {
+            self.events.push(Event::Release(released));
+            self.branch(released + 1);
+            self.events.pop();
+        }
+    }
+
+    /// Lower bounds on the weighted completion time of the jobs completed in the current event
+    /// order and on the time of its last event. The jobs released at or after some release date
+    /// and completed by an event need their total length divided by `m` after that date.
+    fn prefix_bound(&self) -> (f64, f64) {
+        let problem = self.problem;
+        let m = problem.m as f64;
+        let mut work = vec![0.0; self.release_times.len()];
+        let mut t: f64 = 0.0;
+        let mut total = 0.0;
+        for event in &self.events {
+            match *event {
+                Event::Release(g) => t = t.max(self.release_times[g]),
+                Event::Completion(j) => {
+                    t = t.max(problem.releases[j] + problem.instance[j]);
+                    for (g, &r) in self.release_times.iter().enumerate() {
+                        if r <= problem.releases[j] {
+                            work[g] += problem.instance[j];
+                            t = t.max(r + work[g] / m);
+                        }
+                    }
+                    total += problem.weights[j] * t;
+                }
+            }
+        }
+        (total, t)
+    }
+}
+
+/// Position of the amount variable of job `j` in interval `k`, if the job may be processed there.
+fn interval_vars(n: usize, events: &[Event], released_at: &[usize]) -> Vec<Vec<Option<usize>>> {
+    let intervals = events.len() - 1;
+    let mut vars = vec![vec![None; intervals]; n];
+    let mut next = events.len();
+    for (j, job_vars) in vars.iter_mut().enumerate() {
+        let completion = events
+            .iter()
+            .position(|&e| e == Event::Completion(j))
+            .unwrap();
+        for (k, var) in job_vars.iter_mut().enumerate() {
+            if released_at[j] <= k && k < completion {
+                *var = Some(next);
+                next += 1;
+            }
+        }
+    }
+    vars
+}
+
+/// Index of the release event of each job's group.
+fn release_positions(
+    n: usize,
+    problem: &ProblemInstance,
+    release_times: &[f64],
+    events: &[Event],
+) -> Vec<usize> {
+    (0..n)
+        .map(|j| {
+            let g = release_times
+                .iter()
+                .position(|&r| r == problem.releases[j])
+                .unwrap();
+            events.iter().position(|&e| e == Event::Release(g)).unwrap()
+        })
+        .collect()
+}
+
+/// Solves the linear program of a fixed event order. Variables are the event times followed by
+/// the processing amounts; returns the optimal value and solution.
+fn solve_event_order(
+    problem: &ProblemInstance,
+    release_times: &[f64],
+    events: &[Event],
+) -> Option<(f64, Vec<f64>)> {
+    let n = problem.instance.len();
+    let released_at = release_positions(n, problem, release_times, events);
+    let vars = interval_vars(n, events, &released_at);
+    let n_vars = events.len() + vars.iter().flatten().filter(|v| v.is_some()).count();
+    let mut lp = LinearProgram::new(n_vars);
+    let m = problem.m as f64;
+
+    for (k, event) in events.iter().enumerate() {
+        match *event {
+            Event::Release(g) => lp.add_constraint(vec![(k, 1.0)], Relation::Eq, release_times[g]),
+            Event::Completion(j) => lp.set_objective(k, problem.weights[j]),
+        }
+        if k + 1 < events.len() {
+            lp.add_constraint(vec![(k + 1, 1.0), (k, -1.0)], Relation::Ge, 0.0);
+            let mut load = vec![(k + 1, -m), (k, m)];
+            for job_vars in &vars {
+                if let Some(v) = job_vars[k] {
+                    lp.add_constraint(vec![(v, 1.0), (k + 1, -1.0), (k, 1.0)], Relation::Le, 0.0);
+                    load.push((v, 1.0));
+                }
+            }
+            lp.add_constraint(load, Relation::Le, 0.0);
+        }
+    }
+    for (j, job_vars) in vars.iter().enumerate() {
+        let amounts = job_vars.iter().flatten().map(|&v| (v, 1.0)).collect();
+        lp.add_constraint(amounts, Relation::Eq, problem.instance[j]);
+    }
+
+    lp.solve().map(|solution| (solution.value, solution.x))
+}
+
+fn build_schedule(
+    problem: &ProblemInstance,
+    release_times: &[f64],
+    events: &[Event],
+    x: &[f64],
+    ctx: &Context,
+) -> Schedule {
+    let n = problem.instance.len();
+    let released_at = release_positions(n, problem, release_times, events);
+    let vars = interval_vars(n, events, &released_at);
+
+    let mut schedule = Schedule::new(n, ctx.record_profile);
+    for k in 0..events.len() {
+        if k + 1 < events.len() {
+            // event times that differ by rounding only carry rounding amounts, which would
+            // turn into arbitrarily large rates
+            let (start, end) = (x[k], x[k + 1]);
+            if end - start > EVENT_EPS * end.abs().max(1.0) {
+                schedule.process(
+                    start,
+                    end,
+                    vars.iter().enumerate().filter_map(|(j, job_vars)| {
+                        job_vars[k].map(|v| (j, (x[v] / (end - start)).min(1.0)))
+                    }),
+                );
+            }
+        }
+        if let Event::Completion(j) = events[k] {
+            schedule.complete(j, x[k]);
+        }
+    }
+    schedule
+}
+
+/// The exact offline optimum, computed by [`optimum`].
+pub struct Optimum;
+
+impl Scheduler for Optimum {
+    fn name(&self) -> &str {
+        "OPT"
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        false
+    }
+
+    fn supports_switch_cost(&self) -> bool {
+        false
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, _pred: &Prediction, ctx: &Context) -> Schedule {
+        optimum(problem, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        instance::JobDist,
+        prediction::InstancePrediction,
+        scheduler::{AlgorithmSpec, Registry},
+        validate::validate,
+    };
+
+    fn context() -> Context {
+        Context {
+            record_profile: true,
+            ..Context::default()
+        }
+    }
+
+    #[test]
+    fn optimum_is_feasible() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let problem = ProblemInstance::random(5, 2, &mut rng);
+            assert_eq!(validate(&problem, &optimum(&problem, &context())), vec![]);
+        }
+    }
+
+    /// Two events at time 4 up to rounding, which once became a segment with a rate above 1.
+    #[test]
+    fn optimum_merges_events_at_the_same_time() {
+        let problem = ProblemInstance {
+            weights: vec![
+                1.271191132155308,
+                1.9418707150883583,
+                1.2958559078855638,
+                1.5670306322555452,
+                1.3841133654817719,
+                1.4550131243728575,
+                2.77879120281946,
+            ],
+            releases: vec![1.0, 2.0, 2.0, 2.0, 4.0, 2.0, 1.0],
+            m: 2,
+            ..ProblemInstance::single_machine(
+                vec![
+                    108.9484433548188,
+                    1.8389094876439844,
+                    1.0604958170224978,
+                    3.925537499917954,
+                    1.0954961857686463,
+                    3.356760813282545,
+                    1.608698338045049,
+                ]
+                .into(),
+            )
+        };
+        assert_eq!(validate(&problem, &optimum(&problem, &context())), vec![]);
+    }
+
+    #[test]
+    fn optimum_is_at_most_every_algorithm() {
+        let registry = Registry::default();
+        let keys = "prr:0.5,rr,rr-quantum,two-stage:0.5,phase:0.25,pwspt,wdeq,pts:0.5,\
+                    adaptive-pts:0.2,srpt,fifo,setf,wsetf,gittins,sept,wsept,greedy-wdeq,\
+                    greedy-pts:0.5,equi,malleable-pts:0.5,np-wspt,np-restart";
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..10 {
+            let problem = ProblemInstance::random(5, 2, &mut rng);
+            let noise = JobDist::Uniform {
+                low: 0.5,
+                high: 2.0,
+            };
+            let pred: InstancePrediction = problem
+                .instance
+                .jobs
+                .iter()
+                .map(|&length| length * noise.sample(&mut rng))
+                .collect::<Vec<f64>>()
+                .into();
+            let pred = pred.into();
+            let opt = optimum(&problem, &context()).weighted_completion_time(&problem.weights);
+            for key in keys.split(',') {
+                let alg = registry
+                    .create(&key.parse::<AlgorithmSpec>().unwrap())
+                    .unwrap();
+                let value = alg
+                    .schedule(&problem, &pred, &context())
+                    .weighted_completion_time(&problem.weights);
+                assert!(opt <= value * (1.0 + 1e-9), "{}: {} < {}", key, value, opt);
+            }
+        }
+    }
+}
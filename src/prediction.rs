@@ -1,8 +1,68 @@
 use crate::{instance::Instance, Gen};
+use rand::Rng;
 use rand_distr::{Distribution, Normal};
 
 pub type InstancePrediction = Instance;
 
+/// The advice handed to a scheduler alongside the problem.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Prediction {
+    /// A predicted length for every job.
+    Lengths(InstancePrediction),
+    /// A predicted order of the jobs by length.
+    Permutation(PermutationPrediction),
+    /// A predicted distribution of the length of every job.
+    Distribution(DistributionPrediction),
+    /// A predicted length of every job on every machine (unrelated machines).
+    MachineLengths(MachineLengthPrediction),
+}
+
+impl Prediction {
+    /// The predicted lengths; only algorithms that do not accept permutations may call this.
+    pub fn lengths(&self) -> &InstancePrediction {
+        match self {
+            Prediction::Lengths(pred) => pred,
+            _ => panic!("algorithm requires predicted lengths"),
+        }
+    }
+
+    /// Pseudo-lengths that induce the predicted order: the predicted lengths themselves, the
+    /// positions in the predicted permutation, the predicted mean lengths, or the predicted
+    /// lengths on the best machines.
+    pub fn order_keys(&self) -> InstancePrediction {
+        match self {
+            Prediction::Lengths(pred) => pred.clone(),
+            Prediction::Permutation(pred) => pred.ranks(),
+            Prediction::Distribution(pred) => pred.means(),
+            Prediction::MachineLengths(pred) => pred.best(),
+        }
+    }
+}
+
+impl From<InstancePrediction> for Prediction {
+    fn from(pred: InstancePrediction) -> Self {
+        Prediction::Lengths(pred)
+    }
+}
+
+impl From<PermutationPrediction> for Prediction {
+    fn from(pred: PermutationPrediction) -> Self {
+        Prediction::Permutation(pred)
+    }
+}
+
+impl From<DistributionPrediction> for Prediction {
+    fn from(pred: DistributionPrediction) -> Self {
+        Prediction::Distribution(pred)
+    }
+}
+
+impl From<MachineLengthPrediction> for Prediction {
+    fn from(pred: MachineLengthPrediction) -> Self {
+        Prediction::MachineLengths(pred)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct PredGenParams<'a> {
     pub instance: &'a Instance,
@@ -10,18 +70,17 @@ pub struct PredGenParams<'a> {
 }
 
 impl Gen<PredGenParams<'_>> for InstancePrediction {
-    fn generate(params: &PredGenParams) -> InstancePrediction {
-        let mut rng = rand::thread_rng();
-
+    fn generate<R: Rng>(params: &PredGenParams, rng: &mut R) -> InstancePrediction {
         let preds: Vec<f64> = params
             .instance
             .jobs
             .iter()
             .map(|job| {
                 let dist = Normal::new(0.0, params.sigma).unwrap();
-                let mut p = dist.sample(&mut rng) + *job;
-                while p < 1.0 {
-                    p = dist.sample(&mut rng) + *job;
+                // predictions are at least 1, or the length itself for jobs shorter than that
+                let mut p = dist.sample(rng) + *job;
+                while p < job.min(1.0) {
+                    p = dist.sample(rng) + *job;
                 }
                 p
             })
@@ -37,18 +96,16 @@ pub struct ScaledPredGenParams<'a> {
 }
 
 impl Gen<ScaledPredGenParams<'_>> for InstancePrediction {
-    fn generate(params: &ScaledPredGenParams) -> InstancePrediction {
-        let mut rng = rand::thread_rng();
-
+    fn generate<R: Rng>(params: &ScaledPredGenParams, rng: &mut R) -> InstancePrediction {
         let preds: Vec<f64> = params
             .instance
             .jobs
             .iter()
             .map(|job| {
                 let dist = Normal::new(0.0, job.sqrt() * params.sigma_scale).unwrap();
-                let mut p = dist.sample(&mut rng) + *job;
-                while p < 1.0 {
-                    p = dist.sample(&mut rng) + *job;
+                let mut p = dist.sample(rng) + *job;
+                while p < job.min(1.0) {
+                    p = dist.sample(rng) + *job;
                 }
                 p
             })
@@ -57,24 +114,41 @@ impl Gen<ScaledPredGenParams<'_>> for InstancePrediction {
     }
 }
 
+/// Jobs in predicted order, shortest first.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct PermutationPrediction {
     pub permutation: Vec<usize>,
 }
 
-impl Gen<PredGenParams<'_>> for PermutationPrediction {
-    fn generate(params: &PredGenParams) -> PermutationPrediction {
-        let mut rng = rand::thread_rng();
+impl PermutationPrediction {
+    /// The order of predicted lengths, ties broken by job index.
+    pub fn induced_by(pred: &InstancePrediction) -> Self {
+        let mut permutation: Vec<usize> = (0..pred.len()).collect();
+        permutation.sort_by(|&i, &j| pred[i].partial_cmp(&pred[j]).unwrap().then(i.cmp(&j)));
+        PermutationPrediction { permutation }
+    }
 
+    /// Position of every job in the permutation, starting at 1.
+    pub fn ranks(&self) -> InstancePrediction {
+        let mut ranks = vec![0.0; self.permutation.len()];
+        for (pos, &job) in self.permutation.iter().enumerate() {
+            ranks[job] = (pos + 1) as f64;
+        }
+        ranks.into()
+    }
+}
+
+impl Gen<PredGenParams<'_>> for PermutationPrediction {
+    fn generate<R: Rng>(params: &PredGenParams, rng: &mut R) -> PermutationPrediction {
         let preds: Vec<f64> = params
             .instance
             .jobs
             .iter()
             .map(|job| {
                 let dist = Normal::new(*job, params.sigma).unwrap();
-                let mut p = dist.sample(&mut rng);
-                while p < 1.0 {
-                    p = dist.sample(&mut rng);
+                let mut p = dist.sample(rng);
+                while p < job.min(1.0) {
+                    p = dist.sample(rng);
                 }
                 p
             })
@@ -87,3 +161,186 @@ impl Gen<PredGenParams<'_>> for PermutationPrediction {
         }
     }
 }
+
+/// Predicted distribution of the length of a job, given by equally likely samples.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobDistribution {
+    /// In increasing order.
+    samples: Vec<f64>,
+}
+
+impl JobDistribution {
+    pub fn new(mut samples: Vec<f64>) -> Self {
+        assert!(
+            !samples.is_empty(),
+            "a distribution needs at least one sample"
+        );
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        JobDistribution { samples }
+    }
+
+    /// The distribution that is certain of `length`.
+    pub fn point(length: f64) -> Self {
+        JobDistribution {
+            samples: vec![length],
+        }
+    }
+
+    /// The distribution of `lengths` summarised by `points` equally likely points: the means of
+    /// consecutive slices of (nearly) equal size of the sorted lengths.
+    pub fn summarise(lengths: &[f64], points: usize) -> Self {
+        let mut sorted = lengths.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let n = sorted.len();
+        let points = points.max(1).min(n);
+        let samples = (0..points)
+            .map(|k| {
+                let slice = &sorted[k * n / points..(k + 1) * n / points];
+                slice.iter().sum::<f64>() / slice.len() as f64
+            })
+            .collect();
+        JobDistribution { samples }
+    }
+
+    /// The smallest sample such that a fraction of at least `q` of the samples is at most it.
+    pub fn quantile(&self, q: f64) -> f64 {
+        let k = self.samples.len();
+        let rank = (q * k as f64).ceil() as usize;
+        self.samples[rank.max(1).min(k) - 1]
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+
+    /// Gittins index of a job that has received `attained` processing, together with the
+    /// quantum attaining it: the maximum over quanta `d` of the probability that the job
+    /// completes within `d` more processing, divided by the expected processing it receives
+    /// until then, both conditioned on the job not having completed yet. `None` if the job has
+    /// outlived all samples.
+    pub fn gittins(&self, attained: f64) -> Option<(f64, f64)> {
+        // samples reached up to the simulator's tolerance have been ruled out
+        let threshold = attained + 1e-9 * attained.max(1.0);
+        let rest: Vec<f64> = self
+            .samples
+            .iter()
+            .filter(|&&s| s > threshold)
+            .map(|s| s - attained)
+            .collect();
+        let r = rest.len();
+        let mut best: Option<(f64, f64)> = None;
+        let mut best_index = 0.0;
+        let mut prefix = 0.0;
+        for (k, &quantum) in rest.iter().enumerate() {
+            prefix += quantum;
+            if k + 1 < r && rest[k + 1] == quantum {
+                continue;
+            }
+            // (k + 1) / r completes within `quantum`, which costs `prefix / r` for these and
+            // `quantum` for each of the others
+            let index = (k + 1) as f64 / (prefix + (r - k - 1) as f64 * quantum);
+            if index > best_index {
+                best_index = index;
+                best = Some((index, quantum));
+            }
+        }
+        best
+    }
+}
+
+/// A predicted length distribution for every job.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DistributionPrediction {
+    pub jobs: Vec<JobDistribution>,
+}
+
+impl DistributionPrediction {
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Point masses at the predicted lengths.
+    pub fn point(pred: &InstancePrediction) -> Self {
+        DistributionPrediction {
+            jobs: pred
+                .jobs
+                .iter()
+                .map(|&p| JobDistribution::point(p))
+                .collect(),
+        }
+    }
+
+    pub fn means(&self) -> InstancePrediction {
+        self.jobs.iter().map(JobDistribution::mean).collect()
+    }
+
+    /// Fraction of the true lengths that lie in the central interval of probability `level` of
+    /// their predicted distribution; about `level` for calibrated predictions.
+    pub fn coverage(&self, instance: &Instance, level: f64) -> f64 {
+        let tail = (1.0 - level) / 2.0;
+        let covered = self
+            .jobs
+            .iter()
+            .zip(instance)
+            .filter(|(dist, &length)| {
+                dist.quantile(tail) <= length && length <= dist.quantile(1.0 - tail)
+            })
+            .count();
+        covered as f64 / self.len() as f64
+    }
+}
+
+/// A predicted length of every job on every machine, `lengths[j][i]` for job `j` on machine `i`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MachineLengthPrediction {
+    pub lengths: Vec<Vec<f64>>,
+}
+
+impl MachineLengthPrediction {
+    /// The predicted length of every job on the machine where it is predicted to be shortest.
+    pub fn best(&self) -> InstancePrediction {
+        self.lengths
+            .iter()
+            .map(|row| row.iter().cloned().fold(f64::INFINITY, f64::min))
+            .collect()
+    }
+}
+
+/// Distributions of `samples` normally distributed lengths around the point predictions
+/// `center`. Their standard deviation is `spread * sigma`, times the square root of the center if
+/// `relative`. If `center` was drawn with noise `sigma` around the true lengths, `spread = 1`
+/// is approximately calibrated, smaller values are overconfident and larger ones underconfident.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistPredGenParams<'a> {
+    pub center: &'a InstancePrediction,
+    pub sigma: f64,
+    pub relative: bool,
+    pub spread: f64,
+    pub samples: usize,
+}
+
+impl Gen<DistPredGenParams<'_>> for DistributionPrediction {
+    fn generate<R: Rng>(params: &DistPredGenParams, rng: &mut R) -> DistributionPrediction {
+        let jobs = params
+            .center
+            .jobs
+            .iter()
+            .map(|&center| {
+                let scale = if params.relative { center.sqrt() } else { 1.0 };
+                let dist = Normal::new(center, params.spread * params.sigma * scale).unwrap();
+                // as for point predictions, samples are at least 1, or the center if smaller
+                let samples = (0..params.samples)
+                    .map(|_| {
+                        let mut p = dist.sample(rng);
+                        while p < center.min(1.0) {
+                            p = dist.sample(rng);
+                        }
+                        p
+                    })
+                    .collect();
+                JobDistribution::new(samples)
+            })
+            .collect();
+        DistributionPrediction { jobs }
+    }
+}
@@ -1,19 +1,43 @@
 use core::f64;
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use anyhow::Result;
+use anyhow::{bail, Context as _, Result};
 use csv::Writer;
 use indicatif::ParallelProgressIterator;
+use rand::Rng;
 use rayon::prelude::*;
 use serde::Serialize;
 use structopt::StructOpt;
 
 use crate::{
-    algorithms::{phase_algorithm, preferrential_rr, spt, two_stage_schedule},
-    instance::{analyse_instances, Instance, InstanceGenParams, sample_floats, sample_integers},
+    alg_identical::{Fifo, PWspt, Srpt},
+    alg_malleable::{MalleableFifo, MalleablePts},
+    alg_unrelated::{GreedyFifo, GreedyPts},
+    algorithms::{spt, PreferentialRoundRobin},
+    config::Config,
+    dataset::{self, StoredPrediction, StoredProblem},
+    error::{ErrorMeasure, Errors, InversionError, Measure},
+    instance::{
+        analyse_instances, Instance, InstanceGenParams, JobDist, ProblemInstance, SpeedUpDist,
+    },
     job::Job,
-    prediction::{InstancePrediction, PredGenParams, ScaledPredGenParams},
-    Gen, alg_identical::{pwspt, pts, wdeq},
+    learning::LambdaLearner,
+    objective::Objective,
+    opt::{Optimum, MAX_EXACT_JOBS},
+    prediction::{
+        DistPredGenParams, DistributionPrediction, InstancePrediction, MachineLengthPrediction,
+        PermutationPrediction, PredGenParams, Prediction, ScaledPredGenParams,
+    },
+    scheduler::{AlgorithmSpec, Context, Registry, Scheduler},
+    seed::{Seeds, Stream},
+    simulator::SwitchCost,
+    trace::{self, read_swf},
+    validate::validate,
+    Gen,
 };
 
 #[derive(Debug, StructOpt)]
@@ -27,8 +51,41 @@ pub struct Cli {
     )]
     output: PathBuf,
 
+    /// Check the feasibility of every schedule. This records full rate profiles, which is
+    /// slow for large instances.
+    #[structopt(long, global = true)]
+    validate: bool,
+
+    /// Error measures of the predictions to report, out of `simple`, `maxmin` and `inversion`
+    #[structopt(long, global = true, use_delimiter = true)]
+    errors: Vec<Measure>,
+
+    /// Base seed of all random choices; identical command lines then produce identical results
+    #[structopt(long, global = true)]
+    seed: Option<u64>,
+
+    /// Objective reported in `opt`, `lb` and `alg`: the total weighted `completion` or `flow`
+    /// time, the `max-flow` time or the total `stretch`
+    #[structopt(long, global = true, default_value = "completion")]
+    objective: Objective,
+
+    /// Machine time a preempted job needs to resume before it progresses again; also charged
+    /// at the end of every time slice of a shared machine (see `--quantum`). 0 makes
+    /// preemption and time sharing free.
+    #[structopt(long = "switch-cost", global = true, default_value = "0")]
+    switch_cost: f64,
+
+    /// Time slice with which algorithms that share machines realise the shares under
+    /// `--switch-cost`
+    #[structopt(long, global = true, default_value = "1")]
+    quantum: f64,
+
     #[structopt(subcommand)]
     experiment: Experiments,
+
+    /// Number of infeasible schedules found by `--validate`.
+    #[structopt(skip)]
+    infeasible: AtomicUsize,
 }
 
 #[derive(StructOpt, Debug)]
@@ -36,14 +93,57 @@ enum Experiments {
     Exp1(Exp1Parameters),
     Exp2(Exp2Parameters),
     Exp3(Exp3Parameters),
+    /// Permutation predictions: competitive ratio against the inversion error
+    Exp4(Exp4Parameters),
+    /// Distributional predictions: point-prediction algorithms against algorithms for
+    /// predicted length distributions
+    Exp5(Exp5Parameters),
+    /// Instances cut from a workload trace in the Standard Workload Format
+    Trace(TraceParameters),
+    /// Write problems and predictions to the output file (JSON if it ends in `.json`, CSV
+    /// otherwise) for the `--instances` option of the experiments
+    Generate(GenerateParameters),
+    /// Run the experiment described by a TOML or JSON config file
+    Run(RunParameters),
 }
 #[derive(Debug, Serialize)]
 struct Entry {
     name: String,
     param: f64,
     sigma: f64,
+    /// Objective of the reference (see `Cli::reference`); a clairvoyant heuristic rather than
+    /// the optimum on unrelated machines and for malleable jobs, so that `alg / opt` can be
+    /// below 1 there.
     opt: f64,
+    /// Lower bound on the optimum, for experiments where `opt` is not the optimum itself.
+    lb: Option<f64>,
     alg: f64,
+    simple_error: Option<f64>,
+    maxmin_error: Option<f64>,
+    inversion_error: Option<f64>,
+}
+
+impl Entry {
+    fn new(
+        alg: &dyn Scheduler,
+        value: f64,
+        sigma: f64,
+        opt: f64,
+        lb: Option<f64>,
+        errors: Errors,
+    ) -> Entry {
+        Entry {
+            name: alg.name().to_string(),
+            param: alg.param(),
+            sigma,
+            opt,
+            lb,
+            alg: value,
+            simple_error: errors.simple,
+            maxmin_error: errors.maxmin,
+            inversion_error: errors.inversion,
+        }
+    }
 }
 
 #[derive(StructOpt, Debug)]
@@ -51,8 +151,8 @@ struct Exp1Parameters {
     #[structopt(short = "l", long, default_value = "30000")]
     instance_length: usize,
 
-    #[structopt(short = "n")]
-    num_instances: usize,
+    #[structopt(short = "n", required_unless = "instances")]
+    num_instances: Option<usize>,
 
     #[structopt(short = "p", default_value = "5")]
     num_preds: usize,
@@ -71,6 +171,39 @@ struct Exp1Parameters {
 
     #[structopt(short, long = "alpha", default_value = "1.1")]
     alpha: f64,
+
+    /// Distribution of the job lengths, e.g. `weibull:1,0.5` (see README); overrides the alpha
+    /// of the default Pareto distribution
+    #[structopt(long = "length-dist", parse(try_from_str = JobDist::positive))]
+    length_dist: Option<JobDist>,
+
+    /// Pareto shape of the job weights as in exp3; unit weights if omitted
+    #[structopt(short, long = "w-alpha")]
+    weight_alpha: Option<f64>,
+
+    /// Pareto shape of the release dates as in exp3; all jobs released at 0 if omitted
+    #[structopt(short, long = "r-alpha")]
+    release_alpha: Option<f64>,
+
+    /// Distribution of the job weights; overrides `--w-alpha`
+    #[structopt(long = "weight-dist", parse(try_from_str = JobDist::positive))]
+    weight_dist: Option<JobDist>,
+
+    /// Distribution of the release dates, rounded to integers; overrides `--r-alpha`
+    #[structopt(long = "release-dist")]
+    release_dist: Option<JobDist>,
+
+    /// Algorithms to run, given as `key` or `key:param`
+    #[structopt(
+        long = "algs",
+        use_delimiter = true,
+        default_value = "prr:0.1,prr:0.66,two-stage:0.1,two-stage:0.66,phase:0.25,phase:10,rr"
+    )]
+    algorithms: Vec<AlgorithmSpec>,
+
+    /// Read the problems, and their predictions if stored, from this file (see `generate`)
+    #[structopt(long, parse(from_os_str))]
+    instances: Option<PathBuf>,
 }
 
 #[derive(StructOpt, Debug)]
@@ -84,6 +217,11 @@ struct Exp2Parameters {
     #[structopt(short, long = "alpha", default_value = "1.1")]
     alpha: f64,
 
+    /// Distribution of the job lengths, e.g. `weibull:1,0.5` (see README); overrides the alpha
+    /// of the default Pareto distribution
+    #[structopt(long = "length-dist", parse(try_from_str = JobDist::positive))]
+    length_dist: Option<JobDist>,
+
     #[structopt(short, long = "sigma", default_value = "1.0")]
     sigma: f64,
 
@@ -92,6 +230,23 @@ struct Exp2Parameters {
 
     #[structopt(short = "l", long, default_value = "1000")]
     instance_length: usize,
+
+    /// Algorithms to run, given as `key` or `key:param`
+    #[structopt(
+        long = "algs",
+        use_delimiter = true,
+        default_value = "prr:0.1,prr:0.66,two-stage:0.1,two-stage:0.66,phase:0.25,phase:10,rr"
+    )]
+    algorithms: Vec<AlgorithmSpec>,
+
+    /// Also run PRR with a robustification parameter learned from the previous rounds, chosen
+    /// from this grid, e.g. `0,0.25,0.5,0.75,1`
+    #[structopt(long, use_delimiter = true)]
+    learn: Vec<f64>,
+
+    /// Read the problems, and their predictions if stored, from this file (see `generate`)
+    #[structopt(long, parse(from_os_str))]
+    instances: Option<PathBuf>,
 }
 
 #[derive(StructOpt, Debug)]
@@ -99,14 +254,28 @@ struct Exp3Parameters {
     #[structopt(short = "l", long, default_value = "1000")]
     instance_length: usize,
 
-    #[structopt(short = "n")]
-    num_instances: usize,
+    #[structopt(short = "n", required_unless = "instances")]
+    num_instances: Option<usize>,
 
-    #[structopt(short)]
-    m: usize,
+    /// Number of machines; problems read with `--instances` keep their own, which must match
+    #[structopt(short, required_unless_one = &["speeds", "instances"])]
+    m: Option<usize>,
 
-    #[structopt(short, default_value = "1")]
-    scale: usize,
+    /// Speeds of the machines (uniformly related machines), e.g. `2,1,1,0.5`; sets the number
+    /// of machines
+    #[structopt(long, use_delimiter = true)]
+    speeds: Vec<f64>,
+
+    /// Unrelated machines: the length of a job on every machine is its generated length times
+    /// a factor drawn from this distribution, divided by the smallest factor of the job, e.g.
+    /// `uniform:1,4`
+    #[structopt(long, conflicts_with = "speeds", parse(try_from_str = JobDist::positive))]
+    unrelated: Option<JobDist>,
+
+    /// Malleable jobs with speed-up curves drawn from this distribution, e.g. `linear:1,8`,
+    /// `amdahl:0.5,0.95` or `power:0.5` (see README)
+    #[structopt(long, conflicts_with_all = &["speeds", "unrelated"])]
+    speedup: Option<SpeedUpDist>,
 
     #[structopt(short = "p", default_value = "5")]
     num_preds: usize,
@@ -117,7 +286,7 @@ struct Exp3Parameters {
     #[structopt(long = "num-sigma", default_value = "10")]
     num_sigmas: i32,
 
-    #[structopt(short, long = "l-alpha", default_value = "1.1")]
+    #[structopt(long = "l-alpha", default_value = "1.1")]
     length_alpha: f64,
 
     #[structopt(short, long = "w-alpha", default_value = "2.0")]
@@ -125,258 +294,1382 @@ struct Exp3Parameters {
 
     #[structopt(short, long = "r-alpha", default_value = "2.0")]
     release_alpha: f64,
+
+    /// Distribution of the job lengths, e.g. `weibull:1,0.5` (see README); overrides the alpha
+    /// of the default Pareto distribution
+    #[structopt(long = "length-dist", parse(try_from_str = JobDist::positive))]
+    length_dist: Option<JobDist>,
+
+    /// Distribution of the job weights; overrides `--w-alpha`
+    #[structopt(long = "weight-dist", parse(try_from_str = JobDist::positive))]
+    weight_dist: Option<JobDist>,
+
+    /// Distribution of the release dates, which are rounded to integers; overrides `--r-alpha`
+    #[structopt(long = "release-dist")]
+    release_dist: Option<JobDist>,
+
+    /// Algorithms to run, given as `key` or `key:param`
+    #[structopt(
+        long = "algs",
+        use_delimiter = true,
+        default_value = "pts:0.1,pts:0.5,pts:0.8,wdeq"
+    )]
+    algorithms: Vec<AlgorithmSpec>,
+
+    /// Value reported as `opt`: `pwspt` on the true lengths (or the clairvoyant rule suited to
+    /// the objective, see README) or the `exact` optimum, which is only available for small
+    /// instances and the completion time
+    #[structopt(long = "opt", default_value = "pwspt")]
+    reference: Reference,
+
+    /// Read the problems, and their predictions if stored, from this file (see `generate`)
+    #[structopt(long, parse(from_os_str))]
+    instances: Option<PathBuf>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Reference {
+    Pwspt,
+    Exact,
+}
+
+impl FromStr for Reference {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pwspt" => Ok(Reference::Pwspt),
+            "exact" => Ok(Reference::Exact),
+            _ => bail!("unknown reference '{}', expected 'pwspt' or 'exact'", s),
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+struct Exp4Parameters {
+    #[structopt(short = "l", long, default_value = "1000")]
+    instance_length: usize,
+
+    #[structopt(short = "n", required_unless = "instances")]
+    num_instances: Option<usize>,
+
+    #[structopt(short = "p", default_value = "5")]
+    num_preds: usize,
+
+    #[structopt(long = "step-sigma")]
+    step_sigma: Option<f64>,
+
+    #[structopt(long = "base-sigma")]
+    base_sigma: Option<f64>,
+
+    #[structopt(long = "num-sigma", default_value = "10")]
+    num_sigmas: i32,
+
+    #[structopt(short, long = "alpha", default_value = "1.1")]
+    alpha: f64,
+
+    /// Distribution of the job lengths, e.g. `weibull:1,0.5` (see README); overrides the alpha
+    /// of the default Pareto distribution
+    #[structopt(long = "length-dist", parse(try_from_str = JobDist::positive))]
+    length_dist: Option<JobDist>,
+
+    /// Algorithms to run, given as `key` or `key:param`; all of them must accept permutations
+    #[structopt(
+        long = "algs",
+        use_delimiter = true,
+        default_value = "prr:0,prr:0.1,prr:0.5,prr:0.66,rr"
+    )]
+    algorithms: Vec<AlgorithmSpec>,
+
+    /// Read the problems, and their predictions if stored, from this file (see `generate`)
+    #[structopt(long, parse(from_os_str))]
+    instances: Option<PathBuf>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Exp5Parameters {
+    #[structopt(short = "l", long, default_value = "1000")]
+    instance_length: usize,
+
+    #[structopt(short = "n", required_unless = "instances")]
+    num_instances: Option<usize>,
+
+    #[structopt(short = "m", default_value = "1")]
+    m: usize,
+
+    #[structopt(short = "p", default_value = "5")]
+    num_preds: usize,
+
+    #[structopt(long = "step-sigma")]
+    step_sigma: Option<f64>,
+
+    #[structopt(long = "base-sigma")]
+    base_sigma: Option<f64>,
+
+    #[structopt(long = "num-sigma", default_value = "10")]
+    num_sigmas: i32,
+
+    #[structopt(long)]
+    rel_sigma: bool,
+
+    /// Standard deviations of the predicted distributions relative to the noise of their
+    /// centres: 1 is calibrated, smaller values overconfident and larger ones underconfident
+    #[structopt(long, use_delimiter = true, default_value = "0.5,1,2")]
+    spread: Vec<f64>,
+
+    /// Number of samples describing every predicted distribution
+    #[structopt(short = "k", long, default_value = "32")]
+    samples: usize,
+
+    #[structopt(short, long = "alpha", default_value = "1.1")]
+    alpha: f64,
+
+    /// Distribution of the job lengths, e.g. `weibull:1,0.5` (see README); overrides the alpha
+    /// of the default Pareto distribution
+    #[structopt(long = "length-dist", parse(try_from_str = JobDist::positive))]
+    length_dist: Option<JobDist>,
+
+    /// Distribution of the job weights; unit weights if omitted
+    #[structopt(long = "weight-dist", parse(try_from_str = JobDist::positive))]
+    weight_dist: Option<JobDist>,
+
+    /// Distribution of the release dates, rounded to integers; all jobs released at 0 if
+    /// omitted
+    #[structopt(long = "release-dist")]
+    release_dist: Option<JobDist>,
+
+    /// Algorithms to run, given as `key` or `key:param`
+    #[structopt(
+        long = "algs",
+        use_delimiter = true,
+        default_value = "gittins,pts:0,pts:0.5,wdeq"
+    )]
+    algorithms: Vec<AlgorithmSpec>,
+
+    /// Read the problems, and their predictions if stored, from this file (see `generate`);
+    /// stored predictions become the centres of the predicted distributions
+    #[structopt(long, parse(from_os_str))]
+    instances: Option<PathBuf>,
+}
+
+#[derive(StructOpt, Debug)]
+struct TraceParameters {
+    /// SWF file, e.g. from the Parallel Workloads Archive
+    #[structopt(parse(from_os_str))]
+    trace: PathBuf,
+
+    /// Number of consecutive trace jobs per instance
+    #[structopt(short = "l", long, default_value = "1000")]
+    instance_length: usize,
+
+    /// Number of instances (the trace must contain enough jobs)
+    #[structopt(short = "n")]
+    num_instances: usize,
+
+    #[structopt(short, default_value = "1")]
+    m: usize,
+
+    /// Use the requested times of the trace as predictions instead of noisy lengths; jobs
+    /// without a requested time are skipped
+    #[structopt(long)]
+    requested: bool,
+
+    #[structopt(short = "p", default_value = "5")]
+    num_preds: usize,
+
+    #[structopt(long = "base-sigma")]
+    base_sigma: Option<f64>,
+
+    #[structopt(long = "num-sigma", default_value = "10")]
+    num_sigmas: i32,
+
+    /// Algorithms to run, given as `key` or `key:param`
+    #[structopt(
+        long = "algs",
+        use_delimiter = true,
+        default_value = "prr:0.1,prr:0.66,two-stage:0.1,two-stage:0.66,pts:0.5,wdeq"
+    )]
+    algorithms: Vec<AlgorithmSpec>,
+}
+
+#[derive(StructOpt, Debug)]
+struct GenerateParameters {
+    #[structopt(short = "l", long, default_value = "1000")]
+    instance_length: usize,
+
+    #[structopt(short = "n")]
+    num_instances: usize,
+
+    #[structopt(short, default_value = "1")]
+    m: usize,
+
+    #[structopt(short, long = "alpha", default_value = "1.1")]
+    alpha: f64,
+
+    /// Distribution of the job lengths; overrides the alpha of the default Pareto distribution
+    #[structopt(long = "length-dist", parse(try_from_str = JobDist::positive))]
+    length_dist: Option<JobDist>,
+
+    /// Distribution of the job weights; unit weights if omitted
+    #[structopt(long = "weight-dist", parse(try_from_str = JobDist::positive))]
+    weight_dist: Option<JobDist>,
+
+    /// Distribution of the release dates, rounded to integers; all 0 if omitted
+    #[structopt(long = "release-dist")]
+    release_dist: Option<JobDist>,
+
+    /// Predictions per noise parameter
+    #[structopt(short = "p", default_value = "5")]
+    num_preds: usize,
+
+    #[structopt(long = "step-sigma")]
+    step_sigma: Option<f64>,
+
+    /// Noise parameters `base^k - 1`; no predictions are stored without it or `--step-sigma`
+    #[structopt(long = "base-sigma")]
+    base_sigma: Option<f64>,
+
+    #[structopt(long = "num-sigma", default_value = "10")]
+    num_sigmas: i32,
+
+    #[structopt(long)]
+    rel_sigma: bool,
+}
+
+#[derive(StructOpt, Debug)]
+struct RunParameters {
+    /// Experiment specification; JSON if the name ends in `.json`, TOML otherwise
+    #[structopt(parse(from_os_str))]
+    config: PathBuf,
+}
+
+/// Like [`Entry`], but `sigma` is empty for the requested times of the trace.
 #[derive(Debug, Serialize)]
-struct Exp2Entry {
+struct TraceEntry {
     name: String,
     param: f64,
+    sigma: Option<f64>,
     opt: f64,
+    lb: f64,
     alg: f64,
-    round: usize,
+    simple_error: Option<f64>,
+    maxmin_error: Option<f64>,
+    inversion_error: Option<f64>,
 }
 
+#[derive(Debug, Serialize)]
+struct Exp4Entry {
+    name: String,
+    param: f64,
+    sigma: f64,
+    inversion: f64,
+    opt: f64,
+    alg: f64,
+}
 
+#[derive(Debug, Serialize)]
+struct Exp5Entry {
+    name: String,
+    param: f64,
+    sigma: f64,
+    /// Spread of the predicted distributions; empty for algorithms that only use their centres.
+    spread: Option<f64>,
+    /// Fraction of the true lengths in the central 80% of their predicted distributions.
+    coverage: Option<f64>,
+    opt: f64,
+    lb: Option<f64>,
+    alg: f64,
+    simple_error: Option<f64>,
+    maxmin_error: Option<f64>,
+    inversion_error: Option<f64>,
+}
 
-
-
+#[derive(Debug, Serialize)]
+struct Exp2Entry {
+    name: String,
+    param: f64,
+    opt: f64,
+    alg: f64,
+    round: usize,
+    simple_error: Option<f64>,
+    maxmin_error: Option<f64>,
+    inversion_error: Option<f64>,
+}
 
 impl Cli {
+    /// Runs the experiment. With `--validate`, the violations of infeasible schedules are
+    /// written to stderr as they are found and the run fails after writing its results.
     pub fn sample(&self) -> Result<()> {
+        self.run()?;
+        let infeasible = self.infeasible.load(Ordering::Relaxed);
+        if infeasible > 0 {
+            bail!(
+                "{} infeasible schedules, see the violations above",
+                infeasible
+            );
+        }
+        Ok(())
+    }
+
+    fn run(&self) -> Result<()> {
+        let registry = Registry::default();
+        if self.switch_cost < 0.0 || self.quantum <= 0.0 {
+            bail!("the switching cost must be non-negative and the quantum positive");
+        }
+        let ctx = Context {
+            record_profile: self.validate,
+            seed: 0,
+            switching: if self.switch_cost > 0.0 {
+                Some(SwitchCost {
+                    cost: self.switch_cost,
+                    quantum: self.quantum,
+                })
+            } else {
+                None
+            },
+        };
+        let seeds = Seeds::new(self.seed);
         match &self.experiment {
             Experiments::Exp1(params) => {
-                let instance_params = InstanceGenParams {
-                    length: params.instance_length,
-                    alpha: params.alpha,
-                };
-                let instances: Vec<Instance> = (0..params.num_instances)
-                    .map(|_| Instance::generate(&instance_params))
-                    .collect();
-                analyse_instances(&instances);
-                let results: Vec<Entry> = instances
+                let algorithms = self.algorithms(&registry, &params.algorithms)?;
+                let problems = load_or_generate(&params.instances, || {
+                    generate_problems(
+                        &seeds,
+                        params.num_instances.unwrap(),
+                        params.instance_length,
+                        pareto_or(params.length_dist, params.alpha),
+                        params
+                            .weight_dist
+                            .or(params.weight_alpha.map(|alpha| JobDist::Pareto { alpha })),
+                        params
+                            .release_dist
+                            .or(params.release_alpha.map(|alpha| JobDist::Pareto { alpha })),
+                        1,
+                    )
+                })?;
+                let sigmas = required_sigmas(
+                    &problems,
+                    sigma_grid(params.step_sigma, params.base_sigma, params.num_sigmas),
+                )?;
+                analyse_problems(&problems);
+                let count = problems.len() as u64;
+                let results: Vec<Entry> = problems
                     .into_par_iter()
-                    .progress_count(params.num_instances as u64)
-                    .flat_map(|instance| {
-                        let opt = spt(&instance);
-                        (0..params.num_sigmas)
-                            .flat_map(|sigma_num| {
-                                let sigma = if let Some(step_sigma) = params.step_sigma {
-                                    step_sigma * sigma_num as f64
-                                } else {
-                                    params.base_sigma.unwrap().powi(sigma_num) - 1.0
-                                };
-                                (0..params.num_preds)
-                                    .flat_map(|_| {
-                                        let pred: Instance = if params.rel_sigma {
-                                            InstancePrediction::generate(&ScaledPredGenParams {
-                                                sigma_scale: sigma,
-                                                instance: &instance,
-                                            })
-                                        } else {
-                                            InstancePrediction::generate(&PredGenParams {
-                                                sigma: sigma,
-                                                instance: &instance,
-                                            })
-                                        };
-                                        //let simple_error = SimpleError::compute(&instance, &pred);
-                                        //let maxmin_error = MaxMinError::compute(&instance, &pred);
-                                        let mut entries = vec![];
-                                        [0.1, 0.66].iter().for_each(|lambda| {
-                                            entries.push(Entry {
-                                                name: format!("PRR"),
-                                                param: *lambda,
-                                                sigma,
-                                                opt,
-                                                alg: preferrential_rr(&instance, &pred, *lambda),
-                                            });
-                                        });
-
-                                        [0.1, 0.66].iter().for_each(|lambda| {
-                                            entries.push(Entry {
-                                                name: format!("TwoStage"),
-                                                param: *lambda,
-                                                sigma,
-                                                opt,
-                                                alg: two_stage_schedule(&instance, &pred, *lambda),
-                                            });
-                                        });
-
-                                        [0.25, 10.0].iter().for_each(|lambda| {
-                                            let pred = pred.clone();
-                                            let phase = phase_algorithm(&instance, &pred, *lambda);
-                                            entries.push(Entry {
-                                                name: format!("Im et al."),
-                                                param: *lambda,
-                                                sigma,
-                                                opt,
-                                                alg: phase,
-                                            });
-                                        });
-
-                                        entries.push(Entry {
-                                            name: format!("Round-Robin"),
-                                            param: 0.0,
-                                            sigma,
-                                            opt,
-                                            alg: preferrential_rr(&instance, &pred, 1.0),
-                                        });
-                                        entries
-                                    })
-                                    .collect::<Vec<Entry>>()
+                    .enumerate()
+                    .progress_count(count)
+                    .flat_map(|(i, stored)| {
+                        let i = i as u64;
+                        let problem = &stored.problem;
+                        // the reference is only optimal without weights and release dates;
+                        // otherwise report a lower bound as well
+                        let opt = self.reference(problem, &ctx);
+                        let lb = if problem.is_single_machine() {
+                            None
+                        } else {
+                            Some(self.objective.lower_bound(problem))
+                        };
+                        let slots = prediction_slots(&stored, i, &sigmas, params.num_preds);
+                        self.slot_rows(&algorithms, problem, &ctx, &seeds, i, slots, |slot| {
+                            let pred: Prediction = slot
+                                .stored
+                                .take()
+                                .unwrap_or_else(|| {
+                                    noisy_prediction(
+                                        &problem.instance,
+                                        slot.sigma,
+                                        params.rel_sigma,
+                                        &mut seeds.rng(Stream::Prediction, &slot.path),
+                                    )
+                                })
+                                .into();
+                            let errors =
+                                Errors::compute(&self.errors, &problem.instance, pred.lengths());
+                            let sigma = slot.sigma;
+                            (pred, move |alg: &dyn Scheduler, value| {
+                                Entry::new(alg, value, sigma, opt, lb, errors)
                             })
-                            .collect::<Vec<Entry>>()
+                        })
                     })
                     .collect();
 
                 export(&self.output, results)
             }
             Experiments::Exp2(params) => {
-                let results = (0..params.trials)
-                    .into_par_iter()
-                    .progress_count(params.trials as u64)
-                    .flat_map(|_| {
+                let algorithms = self.algorithms(&registry, &params.algorithms)?;
+                if let Some(lambda) = params.learn.iter().find(|l| !(0.0..=1.0).contains(*l)) {
+                    bail!("the --learn grid must lie in [0, 1], got {}", lambda);
+                }
+                let ground_truths: Vec<Instance> = match &params.instances {
+                    Some(path) => dataset::read(path)?
+                        .into_iter()
+                        .map(|stored| stored.problem.instance)
+                        .collect(),
+                    None => {
                         let instance_params = InstanceGenParams {
                             length: params.instance_length,
-                            alpha: params.alpha,
+                            dist: pareto_or(params.length_dist, params.alpha),
                         };
-                        let ground_truth: Instance = Instance::generate(&instance_params);
+                        (0..params.trials as u64)
+                            .map(|trial| {
+                                Instance::generate(
+                                    &instance_params,
+                                    &mut seeds.rng(Stream::Instance, &[trial]),
+                                )
+                            })
+                            .collect()
+                    }
+                };
+                let count = ground_truths.len() as u64;
+                let results = ground_truths
+                    .into_par_iter()
+                    .enumerate()
+                    .progress_count(count)
+                    .flat_map(|(trial, ground_truth)| {
+                        let trial = trial as u64;
                         let mut instances = vec![];
-                        (0..params.timesteps + 1)
-                            .into_iter()
+                        let mut learner = if params.learn.is_empty() {
+                            None
+                        } else {
+                            Some(LambdaLearner::new(params.learn.clone()))
+                        };
+                        (0..=params.timesteps)
                             .flat_map(|round| {
-                                let pred = create_mean_instance(
+                                let path = [trial, round as u64];
+                                let pred: Prediction = create_mean_instance(
                                     &instances,
-                                    params.instance_length,
-                                    params.alpha,
+                                    ground_truth.len(),
+                                    pareto_or(params.length_dist, params.alpha),
+                                    &mut seeds.rng(Stream::Prediction, &path),
+                                )
+                                .into();
+                                let instance: Instance = noisy_prediction(
+                                    &ground_truth,
+                                    params.sigma,
+                                    params.rel_sigma,
+                                    &mut seeds.rng(Stream::Instance, &path),
                                 );
-                                let instance: Instance = if params.rel_sigma {
-                                    InstancePrediction::generate(&ScaledPredGenParams {
-                                        sigma_scale: params.sigma,
-                                        instance: &ground_truth,
-                                    })
-                                } else {
-                                    InstancePrediction::generate(&PredGenParams {
-                                        sigma: params.sigma,
-                                        instance: &ground_truth,
-                                    })
-                                };
-
-                                let opt = spt(&instance);
-                                let mut entries = vec![];
-
-                                [0.1, 0.66].iter().for_each(|lambda| {
-                                    entries.push(Exp2Entry {
-                                        name: format!("PRR"),
-                                        param: *lambda,
-                                        round,
-                                        opt,
-                                        alg: preferrential_rr(&instance, &pred, *lambda),
-                                    });
-                                });
 
-                                [0.1, 0.66].iter().for_each(|lambda| {
-                                    entries.push(Exp2Entry {
-                                        name: format!("TwoStage"),
-                                        param: *lambda,
+                                let errors =
+                                    Errors::compute(&self.errors, &instance, pred.lengths());
+                                let problem = ProblemInstance::single_machine(instance);
+                                let opt = self.reference(&problem, &ctx);
+                                let mut entries = algorithms
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(a, alg)| Exp2Entry {
+                                        name: alg.name().to_string(),
+                                        param: alg.param(),
                                         round,
                                         opt,
-                                        alg: two_stage_schedule(&instance, &pred, *lambda),
-                                    });
-                                });
-
-                                [0.25, 10.0].iter().for_each(|lambda| {
-                                    let pred = pred.clone();
-                                    let phase = phase_algorithm(&instance, &pred, *lambda);
+                                        alg: self.evaluate(
+                                            alg.as_ref(),
+                                            &problem,
+                                            &pred,
+                                            &ctx.with_seed(seeds.seed(
+                                                Stream::Algorithm,
+                                                &[trial, round as u64, a as u64],
+                                            )),
+                                        ),
+                                        simple_error: errors.simple,
+                                        maxmin_error: errors.maxmin,
+                                        inversion_error: errors.inversion,
+                                    })
+                                    .collect::<Vec<Exp2Entry>>();
+                                if let Some(learner) = &mut learner {
+                                    let objectives: Vec<f64> = learner
+                                        .grid()
+                                        .iter()
+                                        .map(|&lambda| {
+                                            self.evaluate(
+                                                &PreferentialRoundRobin {
+                                                    robustification: lambda,
+                                                },
+                                                &problem,
+                                                &pred,
+                                                &ctx,
+                                            )
+                                        })
+                                        .collect();
+                                    let choice = learner.choice();
                                     entries.push(Exp2Entry {
-                                        name: format!("Im et al."),
-                                        param: *lambda,
+                                        name: "Learned PRR".to_string(),
+                                        param: learner.grid()[choice],
                                         round,
                                         opt,
-                                        alg: phase,
+                                        alg: objectives[choice],
+                                        simple_error: errors.simple,
+                                        maxmin_error: errors.maxmin,
+                                        inversion_error: errors.inversion,
                                     });
-                                });
-
-                                entries.push(Exp2Entry {
-                                    name: format!("Round-Robin"),
-                                    param: 0.0,
-                                    round,
-                                    opt,
-                                    alg: preferrential_rr(&instance, &pred, 1.0),
-                                });
+                                    let ratios: Vec<f64> =
+                                        objectives.iter().map(|alg| alg / opt).collect();
+                                    learner.observe(&ratios);
+                                }
 
-                                instances.push(instance);
+                                instances.push(problem.instance);
                                 entries
                             })
                             .collect::<Vec<Exp2Entry>>()
                     })
                     .collect::<Vec<Exp2Entry>>();
                 export(&self.output, results)
-            },
+            }
             Experiments::Exp3(params) => {
-                let instance_params = InstanceGenParams {
-                    length: params.instance_length,
-                    alpha: params.length_alpha,
+                let algorithms = self.algorithms(&registry, &params.algorithms)?;
+                if params.reference == Reference::Exact && self.objective != Objective::Completion {
+                    bail!("the exact optimum is only available for the completion time objective");
+                }
+                let m = match (params.m, params.speeds.len()) {
+                    (Some(m), k) if k > 0 && m != k => {
+                        bail!("-m {} does not match the {} machine speeds", m, k)
+                    }
+                    (Some(m), _) => Some(m),
+                    (None, 0) => None,
+                    (None, k) => Some(k),
                 };
-                let instances: Vec<(Instance, Vec<f64>, Vec<usize>)> = (0..params.num_instances)
-                    .map(|_| (Instance::generate(&instance_params), sample_floats(params.weight_alpha, params.instance_length), sample_integers(params.release_alpha, params.instance_length))
-                )
+                if params.speeds.iter().any(|&speed| speed <= 0.0) {
+                    bail!("machine speeds must be positive");
+                }
+                let mut problems = load_or_generate(&params.instances, || {
+                    generate_problems(
+                        &seeds,
+                        params.num_instances.unwrap(),
+                        params.instance_length,
+                        pareto_or(params.length_dist, params.length_alpha),
+                        Some(pareto_or(params.weight_dist, params.weight_alpha)),
+                        Some(pareto_or(params.release_dist, params.release_alpha)),
+                        m.unwrap(),
+                    )
+                })?;
+                // stored problems keep their machines; the options may only add to them
+                for (i, stored) in problems.iter_mut().enumerate() {
+                    let problem = &stored.problem;
+                    if let Some(m) = m {
+                        if m != problem.m {
+                            bail!(
+                                "problem {} has {} machines, but {} are given",
+                                i,
+                                problem.m,
+                                m
+                            );
+                        }
+                    }
+                    if (!params.speeds.is_empty()
+                        && !problem.is_identical()
+                        && problem.speeds != params.speeds)
+                        || (params.unrelated.is_some() && !problem.is_identical())
+                        || (params.speedup.is_some() && problem.is_malleable())
+                    {
+                        bail!(
+                            "problem {} already has other machines or speed-up curves",
+                            i
+                        );
+                    }
+                    if !params.speeds.is_empty() {
+                        stored.problem.speeds = params.speeds.clone();
+                    }
+                    if let Some(dist) = params.unrelated {
+                        stored.problem.machine_lengths = unrelated_lengths(
+                            &stored.problem,
+                            dist,
+                            &mut seeds.rng(Stream::Instance, &[i as u64, 3]),
+                        );
+                    }
+                    if let Some(dist) = params.speedup {
+                        stored.problem.speedups = dist.sample_n(
+                            stored.problem.instance.len(),
+                            &mut seeds.rng(Stream::Instance, &[i as u64, 4]),
+                        );
+                    }
+                }
+                if problems
+                    .iter()
+                    .any(|stored| !stored.problem.speeds.is_empty())
+                {
+                    if params.reference == Reference::Exact {
+                        bail!("the exact optimum is limited to identical machines");
+                    }
+                    if let Some(alg) = algorithms.iter().find(|alg| !alg.supports_speeds()) {
+                        bail!("{} does not support machine speeds", alg.name());
+                    }
+                }
+                if params.reference == Reference::Exact
+                    && problems.iter().any(|stored| stored.problem.is_malleable())
+                {
+                    bail!("the exact optimum is limited to sequential jobs");
+                }
+                if self.switch_cost > 0.0 {
+                    if params.reference == Reference::Exact {
+                        bail!("the exact optimum does not support switching costs");
+                    }
+                    if problems.iter().any(|stored| {
+                        !stored.problem.is_identical() || stored.problem.is_malleable()
+                    }) {
+                        bail!(
+                            "switching costs are limited to sequential jobs on identical machines"
+                        );
+                    }
+                }
+                if problems.iter().any(|stored| stored.problem.is_unrelated()) {
+                    if params.reference == Reference::Exact {
+                        bail!("the exact optimum is limited to identical machines");
+                    }
+                    if !self.errors.is_empty() {
+                        bail!("error measures are not available on unrelated machines");
+                    }
+                    if let Some(alg) = algorithms.iter().find(|alg| !alg.supports_unrelated()) {
+                        bail!("{} does not support unrelated machines", alg.name());
+                    }
+                }
+                if params.reference == Reference::Exact
+                    && problems
+                        .iter()
+                        .any(|stored| stored.problem.instance.len() > MAX_EXACT_JOBS)
+                {
+                    bail!(
+                        "the exact optimum is limited to {} jobs, use --opt pwspt",
+                        MAX_EXACT_JOBS
+                    );
+                }
+                let sigmas = required_sigmas(
+                    &problems,
+                    sigma_grid(None, params.base_sigma, params.num_sigmas),
+                )?;
+                let results: Vec<Entry> = problems
+                    .into_par_iter()
+                    .enumerate()
+                    .flat_map(|(i, stored)| {
+                        let i = i as u64;
+                        let problem = &stored.problem;
+                        let opt = match params.reference {
+                            Reference::Pwspt => self.reference(problem, &ctx),
+                            Reference::Exact => self.evaluate(
+                                &Optimum,
+                                problem,
+                                &problem.instance.clone().into(),
+                                &ctx,
+                            ),
+                        };
+                        let lb = Some(self.objective.lower_bound(problem));
+                        let slots = prediction_slots(&stored, i, &sigmas, params.num_preds);
+                        self.slot_rows(&algorithms, problem, &ctx, &seeds, i, slots, |slot| {
+                            let mut rng = seeds.rng(Stream::Prediction, &slot.path);
+                            let pred: Prediction = match slot.stored.take() {
+                                Some(lengths) => lengths.into(),
+                                None if problem.is_unrelated() => noisy_machine_prediction(
+                                    &problem.machine_lengths,
+                                    slot.sigma,
+                                    &mut rng,
+                                )
+                                .into(),
+                                None => {
+                                    noisy_prediction(&problem.instance, slot.sigma, false, &mut rng)
+                                        .into()
+                                }
+                            };
+                            let errors = Errors::compute(
+                                &self.errors,
+                                &problem.instance,
+                                &pred.order_keys(),
+                            );
+                            let sigma = slot.sigma;
+                            (pred, move |alg: &dyn Scheduler, value| {
+                                Entry::new(alg, value, sigma, opt, lb, errors)
+                            })
+                        })
+                    })
                     .collect();
-                let results: Vec<Entry> = instances
+
+                export(&self.output, results)
+            }
+            Experiments::Exp4(params) => {
+                let algorithms = self.algorithms(&registry, &params.algorithms)?;
+                if let Some(alg) = algorithms
+                    .iter()
+                    .find(|alg| alg.uses_prediction() && !alg.accepts_permutations())
+                {
+                    bail!(
+                        "{} needs predicted lengths and cannot follow a permutation",
+                        alg.name()
+                    );
+                }
+                let problems = load_or_generate(&params.instances, || {
+                    generate_problems(
+                        &seeds,
+                        params.num_instances.unwrap(),
+                        params.instance_length,
+                        pareto_or(params.length_dist, params.alpha),
+                        None,
+                        None,
+                        1,
+                    )
+                })?;
+                let sigmas = required_sigmas(
+                    &problems,
+                    sigma_grid(params.step_sigma, params.base_sigma, params.num_sigmas),
+                )?;
+                analyse_problems(&problems);
+                let count = problems.len() as u64;
+                let results: Vec<Exp4Entry> = problems
                     .into_par_iter()
-                    .flat_map(|(instance, weights, releases)| {
-                        let opt = pwspt(&instance, &weights, &releases, params.m, params.scale);
-
-                        let wdeq = wdeq(&instance, &weights, &releases, params.m, params.scale);
-                        (0..params.num_sigmas)
-                            .flat_map(|sigma_num| {
-                                let sigma = params.base_sigma.unwrap().powi(sigma_num) - 1.0;
-                                (0..params.num_preds)
-                                    .flat_map(|_| {
-                                        let pred: Instance = InstancePrediction::generate(&PredGenParams {
-                                                sigma,
-                                                instance: &instance,
-                                            });
-                                        
-                                        let mut entries = vec![];
-                                        [0.1, 0.5, 0.8].iter().for_each(|lambda| {
-
-                                            entries.push(Entry {
-                                                name: format!("PTS"),
-                                                param: *lambda,
-                                                sigma,
-                                                opt,
-                                                alg: pts(&instance, &pred, &weights, &releases, *lambda, params.m, params.scale),
-                                            });
-                                        });
+                    .enumerate()
+                    .progress_count(count)
+                    .flat_map(|(i, stored)| {
+                        let i = i as u64;
+                        let problem =
+                            ProblemInstance::single_machine(stored.problem.instance.clone());
+                        let opt = self.reference(&problem, &ctx);
+                        let slots = prediction_slots(&stored, i, &sigmas, params.num_preds);
+                        self.slot_rows(&algorithms, &problem, &ctx, &seeds, i, slots, |slot| {
+                            let permutation = match &slot.stored {
+                                Some(lengths) => PermutationPrediction::induced_by(lengths),
+                                None => PermutationPrediction::generate(
+                                    &PredGenParams {
+                                        sigma: slot.sigma,
+                                        instance: &problem.instance,
+                                    },
+                                    &mut seeds.rng(Stream::Prediction, &slot.path),
+                                ),
+                            };
+                            let inversion =
+                                InversionError::compute(&problem.instance, &permutation);
+                            let sigma = slot.sigma;
+                            (permutation.into(), move |alg: &dyn Scheduler, value| {
+                                Exp4Entry {
+                                    name: alg.name().to_string(),
+                                    param: alg.param(),
+                                    sigma,
+                                    inversion,
+                                    opt,
+                                    alg: value,
+                                }
+                            })
+                        })
+                    })
+                    .collect();
 
-                                        entries.push(Entry {
-                                            name: format!("WDEQ"),
-                                            param: 0.0,
-                                            sigma,
-                                            opt,
-                                            alg: wdeq,
+                export(&self.output, results)
+            }
+            Experiments::Exp5(params) => {
+                let algorithms = self.algorithms(&registry, &params.algorithms)?;
+                let problems = load_or_generate(&params.instances, || {
+                    generate_problems(
+                        &seeds,
+                        params.num_instances.unwrap(),
+                        params.instance_length,
+                        pareto_or(params.length_dist, params.alpha),
+                        params.weight_dist,
+                        params.release_dist,
+                        params.m,
+                    )
+                })?;
+                let sigmas = required_sigmas(
+                    &problems,
+                    sigma_grid(params.step_sigma, params.base_sigma, params.num_sigmas),
+                )?;
+                analyse_problems(&problems);
+                let count = problems.len() as u64;
+                let results: Vec<Exp5Entry> = problems
+                    .into_par_iter()
+                    .enumerate()
+                    .progress_count(count)
+                    .flat_map(|(i, stored)| {
+                        let i = i as u64;
+                        let problem = &stored.problem;
+                        let opt = self.reference(problem, &ctx);
+                        let lb = if problem.is_single_machine() {
+                            None
+                        } else {
+                            Some(self.objective.lower_bound(problem))
+                        };
+                        let fixed =
+                            self.prediction_free_objectives(&algorithms, problem, &ctx, &seeds, i);
+                        prediction_slots(&stored, i, &sigmas, params.num_preds)
+                            .into_iter()
+                            .flat_map(|mut slot| {
+                                let center = slot.stored.take().unwrap_or_else(|| {
+                                    noisy_prediction(
+                                        &problem.instance,
+                                        slot.sigma,
+                                        params.rel_sigma,
+                                        &mut seeds.rng(Stream::Prediction, &slot.path),
+                                    )
+                                });
+                                let errors =
+                                    Errors::compute(&self.errors, &problem.instance, &center);
+                                let entry =
+                                    |alg: &dyn Scheduler,
+                                     spread: Option<f64>,
+                                     coverage: Option<f64>,
+                                     value: f64| Exp5Entry {
+                                        name: alg.name().to_string(),
+                                        param: alg.param(),
+                                        sigma: slot.sigma,
+                                        spread,
+                                        coverage,
+                                        opt,
+                                        lb,
+                                        alg: value,
+                                        simple_error: errors.simple,
+                                        maxmin_error: errors.maxmin,
+                                        inversion_error: errors.inversion,
+                                    };
+                                let point: Prediction = center.clone().into();
+                                let mut entries: Vec<Exp5Entry> = algorithms
+                                    .iter()
+                                    .zip(&fixed)
+                                    .enumerate()
+                                    .filter(|(_, (alg, _))| !alg.accepts_distributions())
+                                    .map(|(a, (alg, fixed))| {
+                                        let value = fixed.unwrap_or_else(|| {
+                                            self.evaluate(
+                                                alg.as_ref(),
+                                                problem,
+                                                &point,
+                                                &ctx.with_seed(slot.algorithm_seed(&seeds, a)),
+                                            )
                                         });
-                                        entries
+                                        entry(alg.as_ref(), None, None, value)
                                     })
-                                    .collect::<Vec<Entry>>()
+                                    .collect();
+                                let [_, s, p] = slot.path;
+                                for (d, &spread) in params.spread.iter().enumerate() {
+                                    let path = [i, s, p, d as u64];
+                                    let pred = DistributionPrediction::generate(
+                                        &DistPredGenParams {
+                                            center: &center,
+                                            sigma: slot.sigma,
+                                            relative: params.rel_sigma,
+                                            spread,
+                                            samples: params.samples,
+                                        },
+                                        &mut seeds.rng(Stream::Prediction, &path),
+                                    );
+                                    let coverage = pred.coverage(&problem.instance, 0.8);
+                                    let pred: Prediction = pred.into();
+                                    for (a, alg) in algorithms.iter().enumerate() {
+                                        if !alg.accepts_distributions() {
+                                            continue;
+                                        }
+                                        let value = self.evaluate(
+                                            alg.as_ref(),
+                                            problem,
+                                            &pred,
+                                            &ctx.with_seed(seeds.seed(
+                                                Stream::Algorithm,
+                                                &[i, s, p, d as u64, a as u64],
+                                            )),
+                                        );
+                                        entries.push(entry(
+                                            alg.as_ref(),
+                                            Some(spread),
+                                            Some(coverage),
+                                            value,
+                                        ));
+                                    }
+                                }
+                                entries
+                            })
+                            .collect::<Vec<Exp5Entry>>()
+                    })
+                    .collect();
+
+                export(&self.output, results)
+            }
+            Experiments::Trace(params) => {
+                let algorithms = self.algorithms(&registry, &params.algorithms)?;
+                if !params.requested && params.base_sigma.is_none() {
+                    bail!("--base-sigma is required unless the requested times are used");
+                }
+                let jobs = read_swf(&params.trace)?;
+                let problems = trace::instances(
+                    &jobs,
+                    params.instance_length,
+                    params.num_instances,
+                    params.m,
+                    params.requested,
+                )
+                .with_context(|| format!("cannot cut instances from {}", params.trace.display()))?;
+                let sigmas =
+                    sigma_grid(None, params.base_sigma, params.num_sigmas).unwrap_or_default();
+                analyse_instances(
+                    &problems
+                        .iter()
+                        .map(|(problem, _)| problem.instance.clone())
+                        .collect::<Vec<Instance>>(),
+                );
+                let count = problems.len() as u64;
+                let results: Vec<TraceEntry> = problems
+                    .into_par_iter()
+                    .enumerate()
+                    .progress_count(count)
+                    .flat_map(|(i, (problem, requested))| {
+                        let i = i as u64;
+                        let opt = self.reference(&problem, &ctx);
+                        let lb = self.objective.lower_bound(&problem);
+                        let slots = match requested {
+                            Some(pred) => vec![PredictionSlot {
+                                sigma: 0.0,
+                                path: [i, 0, 0],
+                                stored: Some(pred),
+                            }],
+                            None => fresh_slots(i, &sigmas, params.num_preds),
+                        };
+                        self.slot_rows(&algorithms, &problem, &ctx, &seeds, i, slots, |slot| {
+                            let pred = slot.stored.take().unwrap_or_else(|| {
+                                InstancePrediction::generate(
+                                    &PredGenParams {
+                                        sigma: slot.sigma,
+                                        instance: &problem.instance,
+                                    },
+                                    &mut seeds.rng(Stream::Prediction, &slot.path),
+                                )
+                            });
+                            let errors = Errors::compute(&self.errors, &problem.instance, &pred);
+                            // the requested times come without a noise parameter
+                            let sigma = Some(slot.sigma).filter(|_| !params.requested);
+                            (pred.into(), move |alg: &dyn Scheduler, value| TraceEntry {
+                                name: alg.name().to_string(),
+                                param: alg.param(),
+                                sigma,
+                                opt,
+                                lb,
+                                alg: value,
+                                simple_error: errors.simple,
+                                maxmin_error: errors.maxmin,
+                                inversion_error: errors.inversion,
                             })
-                            .collect::<Vec<Entry>>()
+                        })
                     })
                     .collect();
 
                 export(&self.output, results)
             }
+            Experiments::Generate(params) => {
+                let mut problems = generate_problems(
+                    &seeds,
+                    params.num_instances,
+                    params.instance_length,
+                    pareto_or(params.length_dist, params.alpha),
+                    params.weight_dist,
+                    params.release_dist,
+                    params.m,
+                );
+                if let Some(sigmas) =
+                    sigma_grid(params.step_sigma, params.base_sigma, params.num_sigmas)
+                {
+                    for (i, stored) in problems.iter_mut().enumerate() {
+                        let slots = prediction_slots(stored, i as u64, &sigmas, params.num_preds);
+                        stored.predictions = slots
+                            .into_iter()
+                            .map(|slot| StoredPrediction {
+                                sigma: slot.sigma,
+                                lengths: noisy_prediction(
+                                    &stored.problem.instance,
+                                    slot.sigma,
+                                    params.rel_sigma,
+                                    &mut seeds.rng(Stream::Prediction, &slot.path),
+                                ),
+                            })
+                            .collect();
+                    }
+                }
+                analyse_problems(&problems);
+                dataset::write(&self.output, &problems)
+            }
+            Experiments::Run(params) => {
+                let config = Config::read(&params.config)?;
+                let program = std::env::args().next().unwrap_or_default();
+                let cli = Cli::from_iter_safe(std::iter::once(program).chain(config.args()?))
+                    .with_context(|| format!("invalid config {}", params.config.display()))?;
+                // settings of the config take precedence over those of the command line
+                let errors = if config.errors.is_empty() {
+                    self.errors.clone()
+                } else {
+                    config
+                        .errors
+                        .iter()
+                        .map(|measure| measure.parse())
+                        .collect::<Result<_>>()?
+                };
+                let objective = match &config.objective {
+                    Some(objective) => objective.parse()?,
+                    None => self.objective,
+                };
+                Cli {
+                    output: config.output.unwrap_or_else(|| self.output.clone()),
+                    validate: self.validate || config.validate,
+                    errors,
+                    seed: config.seed.or(self.seed),
+                    objective,
+                    switch_cost: config.switch_cost.unwrap_or(self.switch_cost),
+                    quantum: config.quantum.unwrap_or(self.quantum),
+                    experiment: cli.experiment,
+                    infeasible: AtomicUsize::new(0),
+                }
+                .sample()
+            }
         }
     }
 }
 
-fn create_mean_instance(instances: &[Instance], instance_length: usize, alpha: f64) -> Instance {
-    if instances.len() > 0 {
+impl Cli {
+    /// The algorithms of `specs`, rejecting those that cannot pay switching costs if there are
+    /// any.
+    fn algorithms(
+        &self,
+        registry: &Registry,
+        specs: &[AlgorithmSpec],
+    ) -> Result<Vec<Box<dyn Scheduler>>> {
+        let algorithms = registry.create_all(specs)?;
+        if self.switch_cost > 0.0 {
+            if let Some(alg) = algorithms.iter().find(|alg| !alg.supports_switch_cost()) {
+                bail!("{} does not support switching costs", alg.name());
+            }
+        }
+        Ok(algorithms)
+    }
+
+    /// Runs `alg` and returns its objective, checking feasibility first if requested and
+    /// reporting the violations of an infeasible schedule.
+    fn evaluate(
+        &self,
+        alg: &dyn Scheduler,
+        problem: &ProblemInstance,
+        pred: &Prediction,
+        ctx: &Context,
+    ) -> f64 {
+        let schedule = alg.schedule(problem, pred, ctx);
+        if self.validate {
+            let violations = validate(problem, &schedule);
+            if !violations.is_empty() {
+                self.infeasible.fetch_add(1, Ordering::Relaxed);
+                let lines: Vec<String> = violations.iter().map(|v| format!("  {}", v)).collect();
+                eprintln!(
+                    "{} ({}) produced an infeasible schedule with {} violations:\n{}",
+                    alg.name(),
+                    alg.param(),
+                    violations.len(),
+                    lines.join("\n")
+                );
+            }
+        }
+        self.objective.value(problem, &schedule)
+    }
+
+    /// Value reported as `opt`: the objective of a clairvoyant rule that is optimal for it on a
+    /// single machine without weights and release dates (SPT), and otherwise P-WSPT for the
+    /// completion time and the weighted flow time, SRPT for the unweighted flow time (optimal on
+    /// a single machine) and the stretch, and FIFO for the maximum flow time (optimal on a
+    /// single machine). On unrelated machines and for malleable jobs, where neither SRPT nor
+    /// P-WSPT apply, it is the clairvoyant counterpart of the algorithms there, which is no
+    /// optimum: FIFO for the maximum flow time and greedy PTS on exact lengths (WSPT) otherwise,
+    /// with the weights `1 / p_j` for the stretch, the flow time weighted by them.
+    fn reference(&self, problem: &ProblemInstance, ctx: &Context) -> f64 {
+        if problem.is_unrelated() || problem.is_malleable() {
+            let rule: Box<dyn Scheduler> = match self.objective {
+                Objective::MaxFlow if problem.is_unrelated() => Box::new(GreedyFifo),
+                Objective::MaxFlow => Box::new(MalleableFifo),
+                _ if problem.is_unrelated() => Box::new(GreedyPts {
+                    robustification: 0.0,
+                }),
+                _ => Box::new(MalleablePts {
+                    robustification: 0.0,
+                }),
+            };
+            let stretch_weighted;
+            let problem = if self.objective == Objective::Stretch {
+                stretch_weighted = ProblemInstance {
+                    weights: problem.instance.jobs.iter().map(|p| 1.0 / p).collect(),
+                    ..problem.clone()
+                };
+                &stretch_weighted
+            } else {
+                problem
+            };
+            return self.evaluate(
+                rule.as_ref(),
+                problem,
+                &problem.instance.clone().into(),
+                ctx,
+            );
+        }
+        let unweighted = problem.weights.iter().all(|&w| w == 1.0);
+        let rule: &dyn Scheduler = match self.objective {
+            Objective::Completion if problem.is_single_machine() => return spt(&problem.instance),
+            Objective::Completion => &PWspt,
+            Objective::Flow if !unweighted => &PWspt,
+            Objective::Flow | Objective::Stretch => &Srpt,
+            Objective::MaxFlow => &Fifo,
+        };
+        self.evaluate(rule, problem, &problem.instance.clone().into(), ctx)
+    }
+
+    /// Rows of `algorithms` on the `instance`-th problem, one per algorithm and slot: `predict`
+    /// draws the prediction of a slot and returns it with the function that turns the objective
+    /// of an algorithm into its row. Algorithms that ignore predictions run only once.
+    #[allow(clippy::too_many_arguments)]
+    fn slot_rows<E, R: Fn(&dyn Scheduler, f64) -> E>(
+        &self,
+        algorithms: &[Box<dyn Scheduler>],
+        problem: &ProblemInstance,
+        ctx: &Context,
+        seeds: &Seeds,
+        instance: u64,
+        slots: Vec<PredictionSlot>,
+        predict: impl Fn(&mut PredictionSlot) -> (Prediction, R),
+    ) -> Vec<E> {
+        let fixed = self.prediction_free_objectives(algorithms, problem, ctx, seeds, instance);
+        slots
+            .into_iter()
+            .flat_map(|mut slot| {
+                let (pred, row) = predict(&mut slot);
+                algorithms
+                    .iter()
+                    .zip(&fixed)
+                    .enumerate()
+                    .map(|(a, (alg, fixed))| {
+                        let value = fixed.unwrap_or_else(|| {
+                            self.evaluate(
+                                alg.as_ref(),
+                                problem,
+                                &pred,
+                                &ctx.with_seed(slot.algorithm_seed(seeds, a)),
+                            )
+                        });
+                        row(alg.as_ref(), value)
+                    })
+                    .collect::<Vec<E>>()
+            })
+            .collect()
+    }
+
+    /// Objectives of the algorithms that ignore predictions, `None` for all others. `instance`
+    /// selects the random streams of the algorithms.
+    fn prediction_free_objectives(
+        &self,
+        algorithms: &[Box<dyn Scheduler>],
+        problem: &ProblemInstance,
+        ctx: &Context,
+        seeds: &Seeds,
+        instance: u64,
+    ) -> Vec<Option<f64>> {
+        let perfect: Prediction = problem.instance.clone().into();
+        algorithms
+            .iter()
+            .enumerate()
+            .map(|(a, alg)| {
+                if alg.uses_prediction() {
+                    None
+                } else {
+                    let seed = seeds.seed(Stream::Algorithm, &[instance, a as u64]);
+                    Some(self.evaluate(alg.as_ref(), problem, &perfect, &ctx.with_seed(seed)))
+                }
+            })
+            .collect()
+    }
+}
+
+/// A prediction to evaluate: stored with the problem, or to be drawn by the experiment from the
+/// prediction stream at `path`.
+struct PredictionSlot {
+    sigma: f64,
+    path: [u64; 3],
+    stored: Option<InstancePrediction>,
+}
+
+impl PredictionSlot {
+    /// Seed of the `a`-th algorithm run on this prediction.
+    fn algorithm_seed(&self, seeds: &Seeds, a: usize) -> u64 {
+        let [i, s, p] = self.path;
+        seeds.seed(Stream::Algorithm, &[i, s, p, a as u64])
+    }
+}
+
+/// The stored predictions of the `i`-th problem, or `num_preds` fresh ones for every noise
+/// parameter if none are stored.
+fn prediction_slots(
+    stored: &StoredProblem,
+    i: u64,
+    sigmas: &[f64],
+    num_preds: usize,
+) -> Vec<PredictionSlot> {
+    if stored.predictions.is_empty() {
+        fresh_slots(i, sigmas, num_preds)
+    } else {
+        stored
+            .predictions
+            .iter()
+            .enumerate()
+            .map(|(k, pred)| PredictionSlot {
+                sigma: pred.sigma,
+                path: [i, u64::MAX, k as u64],
+                stored: Some(pred.lengths.clone()),
+            })
+            .collect()
+    }
+}
+
+/// `num_preds` predictions of the `i`-th problem to draw for every noise parameter.
+fn fresh_slots(i: u64, sigmas: &[f64], num_preds: usize) -> Vec<PredictionSlot> {
+    sigmas
+        .iter()
+        .enumerate()
+        .flat_map(|(s, &sigma)| {
+            (0..num_preds).map(move |p| PredictionSlot {
+                sigma,
+                path: [i, s as u64, p as u64],
+                stored: None,
+            })
+        })
+        .collect()
+}
+
+/// Noise parameters `step * k`, or `base^k - 1` if no step is given, for `k < num`.
+fn sigma_grid(step: Option<f64>, base: Option<f64>, num: i32) -> Option<Vec<f64>> {
+    match (step, base) {
+        (Some(step), _) => Some((0..num).map(|k| step * k as f64).collect()),
+        (None, Some(base)) => Some((0..num).map(|k| base.powi(k) - 1.0).collect()),
+        (None, None) => None,
+    }
+}
+
+/// The noise parameters, which are only optional if all problems come with predictions.
+fn required_sigmas(problems: &[StoredProblem], sigmas: Option<Vec<f64>>) -> Result<Vec<f64>> {
+    match sigmas {
+        Some(sigmas) => Ok(sigmas),
+        None if problems.iter().all(|stored| !stored.predictions.is_empty()) => Ok(vec![]),
+        None => bail!("--base-sigma is required for problems without stored predictions"),
+    }
+}
+
+/// Normally distributed prediction with standard deviation `sigma`, or `sigma` times the square
+/// root of the length if `relative`.
+fn noisy_prediction<R: Rng>(
+    instance: &Instance,
+    sigma: f64,
+    relative: bool,
+    rng: &mut R,
+) -> InstancePrediction {
+    if relative {
+        InstancePrediction::generate(
+            &ScaledPredGenParams {
+                sigma_scale: sigma,
+                instance,
+            },
+            rng,
+        )
+    } else {
+        InstancePrediction::generate(&PredGenParams { sigma, instance }, rng)
+    }
+}
+
+/// Predicted lengths of every job on every machine, drawn independently as by
+/// `noisy_prediction`.
+fn noisy_machine_prediction<R: Rng>(
+    lengths: &[Vec<f64>],
+    sigma: f64,
+    rng: &mut R,
+) -> MachineLengthPrediction {
+    let flat: Instance = lengths.iter().flatten().cloned().collect();
+    let pred = noisy_prediction(&flat, sigma, false, rng);
+    MachineLengthPrediction {
+        lengths: pred
+            .jobs
+            .chunks(lengths.first().map_or(1, Vec::len))
+            .map(<[f64]>::to_vec)
+            .collect(),
+    }
+}
+
+/// Lengths of the jobs of `problem` on its `m` unrelated machines: the length times a factor
+/// from `dist` divided by the smallest factor of the job, so that every job keeps its length on
+/// its best machine.
+fn unrelated_lengths<R: Rng>(
+    problem: &ProblemInstance,
+    dist: JobDist,
+    rng: &mut R,
+) -> Vec<Vec<f64>> {
+    problem
+        .instance
+        .jobs
+        .iter()
+        .map(|&length| {
+            let factors = dist.sample_n(problem.m, rng);
+            let best = factors.iter().cloned().fold(f64::INFINITY, f64::min);
+            factors.iter().map(|f| length * f / best).collect()
+        })
+        .collect()
+}
+
+/// `count` problems with `length` jobs on `m` machines. Weights and (integer) release dates
+/// are drawn from the given distributions, or are 1 and 0 without one.
+fn generate_problems(
+    seeds: &Seeds,
+    count: usize,
+    length: usize,
+    length_dist: JobDist,
+    weight_dist: Option<JobDist>,
+    release_dist: Option<JobDist>,
+    m: usize,
+) -> Vec<StoredProblem> {
+    let instance_params = InstanceGenParams {
+        length,
+        dist: length_dist,
+    };
+    (0..count as u64)
+        .map(|i| {
+            let mut problem = ProblemInstance::single_machine(Instance::generate(
+                &instance_params,
+                &mut seeds.rng(Stream::Instance, &[i]),
+            ));
+            problem.m = m;
+            if let Some(dist) = weight_dist {
+                problem.weights = dist.sample_n(length, &mut seeds.rng(Stream::Instance, &[i, 1]));
+            }
+            if let Some(dist) = release_dist {
+                problem.releases =
+                    dist.sample_integers(length, &mut seeds.rng(Stream::Instance, &[i, 2]));
+            }
+            StoredProblem {
+                problem,
+                predictions: vec![],
+            }
+        })
+        .collect()
+}
+
+fn load_or_generate<F: FnOnce() -> Vec<StoredProblem>>(
+    path: &Option<PathBuf>,
+    generate: F,
+) -> Result<Vec<StoredProblem>> {
+    match path {
+        Some(path) => dataset::read(path),
+        None => Ok(generate()),
+    }
+}
+
+fn analyse_problems(problems: &[StoredProblem]) {
+    analyse_instances(
+        &problems
+            .iter()
+            .map(|stored| stored.problem.instance.clone())
+            .collect::<Vec<Instance>>(),
+    );
+}
+
+fn create_mean_instance<R: Rng>(
+    instances: &[Instance],
+    instance_length: usize,
+    dist: JobDist,
+    rng: &mut R,
+) -> Instance {
+    if !instances.is_empty() {
         let mut lengths: Vec<f64> = Vec::with_capacity(instances.first().unwrap().len());
         for i in 0..instances.first().unwrap().len() {
             let p =
                 instances.iter().map(|instance| instance[i]).sum::<f64>() / instances.len() as f64;
-            if p < 1.0 {
+            if p <= 0.0 {
                 panic!()
             }
             lengths.push(p);
@@ -385,16 +1678,21 @@ fn create_mean_instance(instances: &[Instance], instance_length: usize, alpha: f
     } else {
         let instance_params = InstanceGenParams {
             length: instance_length,
-            alpha: alpha,
+            dist,
         };
-        Instance::generate(&instance_params)
+        Instance::generate(&instance_params, rng)
     }
 }
 
+/// `dist` if given, otherwise Pareto with shape `alpha`.
+fn pareto_or(dist: Option<JobDist>, alpha: f64) -> JobDist {
+    dist.unwrap_or(JobDist::Pareto { alpha })
+}
+
 pub fn create_jobs(instance: &Instance, pred: &InstancePrediction) -> Vec<Job> {
     instance
         .into_iter()
-        .zip(pred.into_iter())
+        .zip(pred)
         .enumerate()
         .map(|(i, (p, y))| Job::new(i, *p, *y))
         .collect()
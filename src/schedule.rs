@@ -0,0 +1,77 @@
+/// Constant processing rates during `[start, end)`; jobs that are not listed are not processed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub rates: Vec<(usize, f64)>,
+}
+
+/// The schedule produced by an algorithm, indexed by job id.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schedule {
+    /// First time a job receives processing, `NaN` while it has not started.
+    pub starts: Vec<f64>,
+    /// Completion time of a job, `NaN` while it has not completed.
+    pub completions: Vec<f64>,
+    /// Piecewise-constant processing rates in chronological order. Only recorded on request,
+    /// since time sharing yields quadratically many entries.
+    pub profile: Vec<Segment>,
+    /// Processing of a job that was lost because it was killed and restarted.
+    pub lost: Vec<f64>,
+    record_profile: bool,
+}
+
+impl Schedule {
+    pub fn new(n: usize, record_profile: bool) -> Self {
+        Schedule {
+            starts: vec![f64::NAN; n],
+            completions: vec![f64::NAN; n],
+            profile: vec![],
+            lost: vec![0.0; n],
+            record_profile,
+        }
+    }
+
+    /// Records that the jobs are processed at the given rates during `[start, end)`.
+    pub fn process<I: IntoIterator<Item = (usize, f64)>>(
+        &mut self,
+        start: f64,
+        end: f64,
+        rates: I,
+    ) {
+        if end <= start {
+            return;
+        }
+        let rates: Vec<(usize, f64)> = rates.into_iter().filter(|(_, r)| *r > 0.0).collect();
+        for &(id, _) in &rates {
+            if self.starts[id].is_nan() {
+                self.starts[id] = start;
+            }
+        }
+        if self.record_profile && !rates.is_empty() {
+            self.profile.push(Segment { start, end, rates });
+        }
+    }
+
+    /// Records that job `id` was killed after receiving `amount` of processing since its last
+    /// start, which it has to receive again.
+    pub fn restart(&mut self, id: usize, amount: f64) {
+        self.lost[id] += amount;
+    }
+
+    pub fn complete(&mut self, id: usize, time: f64) {
+        assert!(self.completions[id].is_nan(), "job {} completed twice", id);
+        self.completions[id] = time;
+        if self.starts[id].is_nan() {
+            self.starts[id] = time;
+        }
+    }
+
+    pub fn weighted_completion_time(&self, weights: &[f64]) -> f64 {
+        self.completions
+            .iter()
+            .zip(weights)
+            .map(|(c, w)| w * c)
+            .sum()
+    }
+}
@@ -0,0 +1,373 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    alg_identical::{AdaptivePts, Fifo, PWspt, Pts, QuantumRoundRobin, Setf, Srpt, Wdeq},
+    alg_malleable::{Equi, MalleableFifo, MalleablePts},
+    alg_nonpreemptive::{NpRestart, NpWspt},
+    alg_unrelated::{GreedyFifo, GreedyPts, GreedyWdeq},
+    algorithms::{PhaseAlgorithm, PreferentialRoundRobin, RoundRobin, TwoStage},
+    instance::ProblemInstance,
+    prediction::Prediction,
+    schedule::Schedule,
+    simulator::SwitchCost,
+    stochastic::{Gittins, Knowledge, Sept},
+};
+
+/// Settings shared by all schedulers of an experiment run.
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    /// Record the full processing-rate profile of each schedule.
+    pub record_profile: bool,
+    /// Seed of the random choices an algorithm makes (e.g. the sampling of the phase algorithm).
+    pub seed: u64,
+    /// Overhead of preemptions and time sharing in the simulator, none if free.
+    pub switching: Option<SwitchCost>,
+}
+
+impl Context {
+    /// The same settings with the random choices drawn from `seed`.
+    pub fn with_seed(&self, seed: u64) -> Context {
+        Context {
+            seed,
+            ..self.clone()
+        }
+    }
+
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
+    }
+}
+
+pub trait Scheduler: Send + Sync {
+    /// Name under which the algorithm is reported.
+    fn name(&self) -> &str;
+
+    /// The algorithm's parameter (robustification, epsilon, ...), `0.0` if it has none.
+    fn param(&self) -> f64;
+
+    /// Whether the outcome depends on the prediction; if not, experiments run the algorithm
+    /// once per instance.
+    fn uses_prediction(&self) -> bool {
+        true
+    }
+
+    /// Whether the algorithm only needs the predicted order of the jobs, so that it also runs
+    /// on [`Prediction::Permutation`].
+    fn accepts_permutations(&self) -> bool {
+        false
+    }
+
+    /// Whether the algorithm makes use of predicted length distributions
+    /// ([`Prediction::Distribution`]); exp5 hands all others the point predictions they are
+    /// centred on.
+    fn accepts_distributions(&self) -> bool {
+        false
+    }
+
+    /// Whether the algorithm handles uniformly related machines (`ProblemInstance::speeds`);
+    /// all others assume identical machines.
+    fn supports_speeds(&self) -> bool {
+        false
+    }
+
+    /// Whether the algorithm handles unrelated machines (`ProblemInstance::machine_lengths`)
+    /// and predicted lengths per job and machine ([`Prediction::MachineLengths`]).
+    fn supports_unrelated(&self) -> bool {
+        false
+    }
+
+    /// Whether the algorithm runs on the simulator and therefore pays the switching costs of
+    /// [`Context::switching`].
+    fn supports_switch_cost(&self) -> bool {
+        true
+    }
+
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule;
+}
+
+/// Builds a scheduler from its parameter, if one is given, and rejects invalid parameters.
+pub type Constructor = fn(Option<f64>) -> Result<Box<dyn Scheduler>>;
+
+/// Lookup table from algorithm keys (as used on the command line) to constructors.
+pub struct Registry {
+    algorithms: BTreeMap<&'static str, Constructor>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry {
+            algorithms: BTreeMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, key: &'static str, constructor: Constructor) {
+        self.algorithms.insert(key, constructor);
+    }
+
+    pub fn create(&self, spec: &AlgorithmSpec) -> Result<Box<dyn Scheduler>> {
+        let constructor = self.algorithms.get(spec.key.as_str()).ok_or_else(|| {
+            anyhow!(
+                "unknown algorithm '{}', available: {}",
+                spec.key,
+                self.algorithms
+                    .keys()
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?;
+        constructor(spec.param).with_context(|| format!("invalid algorithm '{}'", spec.key))
+    }
+
+    pub fn create_all(&self, specs: &[AlgorithmSpec]) -> Result<Vec<Box<dyn Scheduler>>> {
+        specs.iter().map(|spec| self.create(spec)).collect()
+    }
+}
+
+/// The parameter of an algorithm that has no default.
+fn required(param: Option<f64>) -> Result<f64> {
+    param.ok_or_else(|| anyhow!("a parameter is required, e.g. `key:0.5`"))
+}
+
+/// A positive parameter, such as an epsilon or a budget.
+fn positive(value: f64) -> Result<f64> {
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        bail!("the parameter must be positive, got {}", value)
+    }
+}
+
+/// A positive parameter, or `default` if none is given.
+fn positive_or(param: Option<f64>, default: f64) -> Result<f64> {
+    param.map_or(Ok(default), positive)
+}
+
+/// A parameter in `[0, 1]`, such as a robustification parameter.
+fn unit_interval(value: f64) -> Result<f64> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        bail!("the parameter must be between 0 and 1, got {}", value)
+    }
+}
+
+/// A number of points, or the default of the algorithm if none is given.
+fn points(param: Option<f64>) -> Result<Option<usize>> {
+    match param {
+        None => Ok(None),
+        Some(value) if value >= 1.0 && value.fract() == 0.0 => Ok(Some(value as usize)),
+        Some(value) => bail!(
+            "the number of points must be a positive integer, got {}",
+            value
+        ),
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Registry::new();
+        registry.register("prr", |lambda| {
+            Ok(Box::new(PreferentialRoundRobin {
+                robustification: unit_interval(required(lambda)?)?,
+            }))
+        });
+        registry.register("rr", |_| Ok(Box::new(RoundRobin)));
+        registry.register("rr-quantum", |quantum| {
+            Ok(Box::new(QuantumRoundRobin {
+                quantum: positive_or(quantum, 1.0)?,
+            }))
+        });
+        registry.register("two-stage", |lambda| {
+            Ok(Box::new(TwoStage {
+                lambda: positive(required(lambda)?)?,
+            }))
+        });
+        registry.register("phase", |epsilon| {
+            Ok(Box::new(PhaseAlgorithm {
+                epsilon: positive(required(epsilon)?)?,
+            }))
+        });
+        registry.register("pwspt", |_| Ok(Box::new(PWspt)));
+        registry.register("wdeq", |_| Ok(Box::new(Wdeq)));
+        registry.register("pts", |lambda| {
+            Ok(Box::new(Pts {
+                robustification: unit_interval(required(lambda)?)?,
+            }))
+        });
+        registry.register("adaptive-pts", |initial| {
+            Ok(Box::new(AdaptivePts {
+                initial: unit_interval(required(initial)?)?,
+            }))
+        });
+        registry.register("srpt", |_| Ok(Box::new(Srpt)));
+        registry.register("fifo", |_| Ok(Box::new(Fifo)));
+        registry.register("setf", |_| Ok(Box::new(Setf { weighted: false })));
+        registry.register("wsetf", |_| Ok(Box::new(Setf { weighted: true })));
+        registry.register("gittins", |_| {
+            Ok(Box::new(Gittins {
+                knowledge: Knowledge::Prediction,
+            }))
+        });
+        registry.register("gittins-workload", |param| {
+            Ok(Box::new(Gittins {
+                knowledge: Knowledge::workload(points(param)?),
+            }))
+        });
+        registry.register("sept", |_| {
+            Ok(Box::new(Sept {
+                weighted: false,
+                knowledge: Knowledge::Prediction,
+            }))
+        });
+        registry.register("wsept", |_| {
+            Ok(Box::new(Sept {
+                weighted: true,
+                knowledge: Knowledge::Prediction,
+            }))
+        });
+        registry.register("wsept-workload", |param| {
+            Ok(Box::new(Sept {
+                weighted: true,
+                knowledge: Knowledge::workload(points(param)?),
+            }))
+        });
+        registry.register("greedy-wdeq", |_| Ok(Box::new(GreedyWdeq)));
+        registry.register("greedy-pts", |lambda| {
+            Ok(Box::new(GreedyPts {
+                robustification: unit_interval(required(lambda)?)?,
+            }))
+        });
+        registry.register("greedy-fifo", |_| Ok(Box::new(GreedyFifo)));
+        registry.register("equi", |_| Ok(Box::new(Equi)));
+        registry.register("malleable-pts", |lambda| {
+            Ok(Box::new(MalleablePts {
+                robustification: unit_interval(required(lambda)?)?,
+            }))
+        });
+        registry.register("malleable-fifo", |_| Ok(Box::new(MalleableFifo)));
+        registry.register("np-wspt", |_| Ok(Box::new(NpWspt)));
+        registry.register("np-restart", |initial| {
+            Ok(Box::new(NpRestart {
+                initial: positive_or(initial, 1.0)?,
+            }))
+        });
+        registry
+    }
+}
+
+/// An algorithm selection of the form `key` or `key:param`, e.g. `prr:0.66`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlgorithmSpec {
+    pub key: String,
+    /// The parameter if given; the constructor applies the algorithm's default otherwise.
+    pub param: Option<f64>,
+}
+
+impl FromStr for AlgorithmSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some((key, param)) => Ok(AlgorithmSpec {
+                key: key.trim().to_string(),
+                param: Some(
+                    param
+                        .trim()
+                        .parse()
+                        .map_err(|_| anyhow!("invalid parameter in algorithm '{}'", s))?,
+                ),
+            }),
+            None => Ok(AlgorithmSpec {
+                key: s.trim().to_string(),
+                param: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::mpsc::{self, RecvTimeoutError},
+        thread,
+        time::Duration,
+    };
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::validate::{validate, Violation};
+
+    #[test]
+    fn algorithms_finish_under_switching_costs() {
+        let registry = Registry::default();
+        let ctx = Context {
+            record_profile: true,
+            switching: Some(SwitchCost {
+                cost: 0.5,
+                quantum: 0.3,
+            }),
+            ..Context::default()
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        let problems: Vec<ProblemInstance> = (0..3)
+            .map(|_| ProblemInstance::random(40, 2, &mut rng))
+            .collect();
+
+        for (&key, constructor) in &registry.algorithms {
+            let alg = constructor(Some(0.5))
+                .or_else(|_| constructor(None))
+                .unwrap();
+            if !alg.supports_switch_cost() {
+                continue;
+            }
+            let (problems, ctx) = (problems.clone(), ctx.clone());
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || {
+                let violations: Vec<Violation> = problems
+                    .iter()
+                    .flat_map(|problem| {
+                        let pred = problem.instance.clone().into();
+                        validate(problem, &alg.schedule(problem, &pred, &ctx))
+                    })
+                    .collect();
+                sender.send(violations).unwrap();
+            });
+            match receiver.recv_timeout(Duration::from_secs(20)) {
+                Ok(violations) => assert_eq!(violations, vec![], "{}", key),
+                Err(RecvTimeoutError::Timeout) => panic!("{} did not finish in time", key),
+                Err(RecvTimeoutError::Disconnected) => panic!("{} panicked", key),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_parameters_out_of_range() {
+        let registry = Registry::default();
+        let create = |spec: &str| registry.create(&spec.parse().unwrap());
+        for spec in &[
+            "prr:1.5",
+            "prr:-0.1",
+            "pts:1.5",
+            "pts:-0.5",
+            "adaptive-pts:2",
+            "greedy-pts:-0.1",
+            "malleable-pts:1.1",
+            "two-stage:-1",
+            "two-stage:0",
+            "phase:0",
+            "rr-quantum:0",
+            "np-restart:-1",
+            "gittins-workload:2.5",
+        ] {
+            assert!(create(spec).is_err(), "{}", spec);
+        }
+        for spec in &["prr:0", "pts:1", "two-stage:2", "phase:10", "rr-quantum"] {
+            assert!(create(spec).is_ok(), "{}", spec);
+        }
+    }
+}
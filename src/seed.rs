@@ -0,0 +1,76 @@
+//! Deterministic random number streams. Every instance, prediction and algorithm run draws from
+//! its own stream, derived from the base seed and the position of the run in the experiment, so
+//! the results do not depend on the order in which rayon processes them.
+
+use rand::{rngs::StdRng, SeedableRng};
+
+/// What a stream is used for, so that e.g. instance 3 and prediction 3 never share a stream.
+#[derive(Clone, Copy, Debug)]
+pub enum Stream {
+    Instance = 1,
+    Prediction = 2,
+    Algorithm = 3,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Seeds {
+    base: u64,
+}
+
+impl Seeds {
+    /// Streams derived from `seed`, or from a random base seed if none is given.
+    pub fn new(seed: Option<u64>) -> Seeds {
+        Seeds {
+            base: seed.unwrap_or_else(rand::random),
+        }
+    }
+
+    /// Seed of the stream at `path`, e.g. `[instance, sigma, prediction]`.
+    pub fn seed(&self, stream: Stream, path: &[u64]) -> u64 {
+        path.iter()
+            .fold(splitmix64(self.base ^ stream as u64), |state, &step| {
+                splitmix64(state ^ splitmix64(step))
+            })
+    }
+
+    pub fn rng(&self, stream: Stream, path: &[u64]) -> StdRng {
+        StdRng::seed_from_u64(self.seed(stream, path))
+    }
+}
+
+/// Finalizer of the SplitMix64 generator, which spreads nearby inputs over the whole range.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn draws(seeds: Seeds, stream: Stream, path: &[u64]) -> Vec<u64> {
+        let mut rng = seeds.rng(stream, path);
+        (0..4).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn fixed_seed_gives_identical_streams() {
+        let (a, b) = (Seeds::new(Some(7)), Seeds::new(Some(7)));
+        assert_eq!(
+            draws(a, Stream::Instance, &[3, 1]),
+            draws(b, Stream::Instance, &[3, 1])
+        );
+        // other streams, paths and base seeds differ
+        let reference = draws(a, Stream::Instance, &[3, 1]);
+        assert_ne!(reference, draws(a, Stream::Prediction, &[3, 1]));
+        assert_ne!(reference, draws(a, Stream::Instance, &[1, 3]));
+        assert_ne!(reference, draws(a, Stream::Instance, &[3]));
+        assert_ne!(
+            reference,
+            draws(Seeds::new(Some(8)), Stream::Instance, &[3, 1])
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{
+    instance::ProblemInstance, prediction::InstancePrediction, schedule::Schedule,
+    scheduler::Context,
+};
+
+/// Relative tolerance below which a job's remaining length counts as zero.
+const EPS: f64 = 1e-9;
+
+/// Overhead of preemptions and time sharing on identical machines; without it, both are free.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SwitchCost {
+    /// Machine time a preempted job needs when it resumes before it makes progress again. The
+    /// first start of a job is free.
+    pub cost: f64,
+    /// Time slice with which a share of a machine is realised. Every slice ends with a switch,
+    /// so that a job at a rate below 1 progresses at `quantum / (quantum + cost)` of that rate.
+    pub quantum: f64,
+}
+
+impl SwitchCost {
+    /// The rate at which a job with the given rate progresses, `setup` being its remaining
+    /// machine time to resume.
+    fn progress(&self, rate: f64, setup: f64) -> f64 {
+        if setup > 0.0 {
+            0.0
+        } else if rate < 1.0 - EPS {
+            rate * self.quantum / (self.quantum + self.cost)
+        } else {
+            rate
+        }
+    }
+}
+
+/// A released, uncompleted job as seen by a policy.
+#[derive(Clone, Copy, Debug)]
+pub struct SimJob {
+    pub id: usize,
+    pub weight: f64,
+    pub pred: f64,
+    /// Remaining processing time; non-clairvoyant policies must not look at it.
+    pub remaining: f64,
+    /// Processing received so far.
+    pub attained: f64,
+}
+
+/// An online scheduling policy for the event-driven simulation. Between two events all
+/// processing rates are constant.
+pub trait Policy {
+    /// Processing rates of `jobs` (same order), valid until the next event.
+    fn rates(&mut self, time: f64, jobs: &[SimJob]) -> Vec<f64>;
+
+    /// Duration after which the policy wants to recompute its rates even if no job is
+    /// released or completed in the meantime. `rates` are the rates at which the jobs progress,
+    /// which are below the policy's rates under switching costs.
+    fn horizon(&self, _time: f64, _jobs: &[SimJob], _rates: &[f64]) -> Option<f64> {
+        None
+    }
+
+    /// Jobs (by id) that the policy kills at `time`, before it is asked for rates. A killed job
+    /// loses its processing and has to be processed from the start again.
+    fn killed(&mut self, _time: f64, _jobs: &[SimJob]) -> Vec<usize> {
+        vec![]
+    }
+
+    /// Called when `job` completes; its `attained` processing is the revealed length.
+    fn completed(&mut self, _time: f64, _job: &SimJob) {}
+}
+
+/// Runs `policy` on `problem`. Events are job releases, job completions, the ends of resumes
+/// under `ctx.switching` and the policy's own horizons, so the running time depends on the
+/// number of events only. The profile records the rates at which jobs progress, including the
+/// processing that killed jobs lose.
+pub fn simulate<P: Policy>(
+    problem: &ProblemInstance,
+    pred: &InstancePrediction,
+    policy: &mut P,
+    ctx: &Context,
+) -> Schedule {
+    let n = problem.instance.len();
+    let mut pending: Vec<usize> = (0..n).collect();
+    pending.sort_by(|&i, &j| {
+        problem.releases[j]
+            .partial_cmp(&problem.releases[i])
+            .unwrap()
+            .then(j.cmp(&i))
+    });
+
+    let mut t = 0.0;
+    let mut schedule = Schedule::new(n, ctx.record_profile);
+    let mut jobs: Vec<SimJob> = vec![];
+    // with switching costs: remaining machine time to resume, whether the job ran in the last
+    // interval, and whether it has ever run, all aligned with `jobs`
+    let switching = ctx.switching;
+    assert!(
+        switching.is_none() || (problem.is_identical() && !problem.is_malleable()),
+        "switching costs are limited to sequential jobs on identical machines"
+    );
+    let mut setup: Vec<f64> = vec![];
+    let mut running: Vec<bool> = vec![];
+    let mut started: Vec<bool> = vec![];
+
+    while !jobs.is_empty() || !pending.is_empty() {
+        while let Some(&j) = pending.last() {
+            if problem.releases[j] > t {
+                break;
+            }
+            jobs.push(SimJob {
+                id: j,
+                weight: problem.weights[j],
+                pred: pred[j],
+                remaining: problem.instance[j],
+                attained: 0.0,
+            });
+            setup.push(0.0);
+            running.push(false);
+            started.push(false);
+            pending.pop();
+        }
+        let next_release = pending.last().map(|&j| problem.releases[j]);
+
+        if jobs.is_empty() {
+            t = next_release.unwrap();
+            continue;
+        }
+
+        for id in policy.killed(t, &jobs) {
+            let job = jobs.iter_mut().find(|job| job.id == id).unwrap();
+            schedule.restart(id, job.attained);
+            job.remaining += job.attained;
+            job.attained = 0.0;
+        }
+
+        let rates = policy.rates(t, &jobs);
+        assert_eq!(rates.len(), jobs.len());
+        let progress: Vec<f64> = match switching {
+            None => rates.clone(),
+            Some(switching) => (0..jobs.len())
+                .map(|idx| {
+                    if rates[idx] > 0.0 && !running[idx] && started[idx] {
+                        setup[idx] = switching.cost;
+                    }
+                    running[idx] = rates[idx] > 0.0;
+                    started[idx] |= running[idx];
+                    switching.progress(rates[idx], setup[idx])
+                })
+                .collect(),
+        };
+
+        let mut dt = f64::INFINITY;
+        let mut first = None;
+        for (idx, (job, &rate)) in jobs.iter().zip(&progress).enumerate() {
+            if rate > 0.0 && job.remaining / rate < dt {
+                dt = job.remaining / rate;
+                first = Some(idx);
+            }
+        }
+        let mut resumed = None;
+        for idx in 0..jobs.len() {
+            if setup[idx] > 0.0 && rates[idx] > 0.0 && setup[idx] / rates[idx] < dt {
+                dt = setup[idx] / rates[idx];
+                first = None;
+                resumed = Some(idx);
+            }
+        }
+        if let Some(release) = next_release {
+            if release - t < dt {
+                dt = release - t;
+                first = None;
+                resumed = None;
+            }
+        }
+        if let Some(horizon) = policy.horizon(t, &jobs, &progress) {
+            if horizon < dt {
+                dt = horizon;
+                first = None;
+                resumed = None;
+            }
+        }
+        assert!(dt.is_finite(), "policy idles forever at time {}", t);
+
+        schedule.process(
+            t,
+            t + dt,
+            jobs.iter().zip(&progress).map(|(job, &r)| (job.id, r)),
+        );
+        t += dt;
+        for (job, &rate) in jobs.iter_mut().zip(&progress) {
+            job.remaining -= rate * dt;
+            job.attained += rate * dt;
+        }
+        if let Some(idx) = first {
+            jobs[idx].attained += jobs[idx].remaining;
+            jobs[idx].remaining = 0.0;
+        }
+        if switching.is_some() {
+            for idx in 0..jobs.len() {
+                if setup[idx] > 0.0 {
+                    setup[idx] = (setup[idx] - rates[idx] * dt).max(0.0);
+                }
+            }
+            if let Some(idx) = resumed {
+                setup[idx] = 0.0;
+            }
+        }
+
+        let mut idx = 0;
+        while idx < jobs.len() {
+            let job = jobs[idx];
+            if job.remaining <= EPS * (job.remaining + job.attained).max(1.0) {
+                policy.completed(t, &job);
+                schedule.complete(job.id, t);
+                jobs.remove(idx);
+                setup.remove(idx);
+                running.remove(idx);
+                started.remove(idx);
+            } else {
+                idx += 1;
+            }
+        }
+    }
+
+    schedule
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+    use rand_distr::{Distribution, Pareto, Uniform};
+
+    use crate::{
+        alg_identical::{pts, pwspt, wdeq},
+        algorithms::{preferrential_rr, spt},
+        instance::ProblemInstance,
+        prediction::InstancePrediction,
+        scheduler::Context,
+    };
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    /// Single-machine problems with Pareto lengths and unit weights, all released at 0.
+    fn problems() -> Vec<ProblemInstance> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let pareto = Pareto::new(1.0, 1.1).unwrap();
+        (0..10)
+            .map(|_| {
+                ProblemInstance::single_machine((0..20).map(|_| pareto.sample(&mut rng)).collect())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pwspt_on_one_machine_is_spt() {
+        for problem in problems() {
+            let schedule = pwspt(&problem, &Context::default());
+            assert_close(
+                schedule.weighted_completion_time(&problem.weights),
+                spt(&problem.instance),
+            );
+        }
+    }
+
+    #[test]
+    fn wdeq_on_one_machine_is_round_robin() {
+        for problem in problems() {
+            // the i-th shortest job completes after every job has received its length, and
+            // the longer ones have received it as well
+            let mut lengths = problem.instance.jobs.clone();
+            lengths.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let n = lengths.len();
+            let expected: f64 = lengths
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (2 * (n - i) - 1) as f64 * p)
+                .sum();
+            let schedule = wdeq(&problem, &Context::default());
+            assert_close(
+                schedule.weighted_completion_time(&problem.weights),
+                expected,
+            );
+        }
+    }
+
+    #[test]
+    fn pts_on_one_machine_is_preferential_round_robin() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let noise = Uniform::new(0.5, 2.0);
+        for problem in problems() {
+            let pred: InstancePrediction = problem
+                .instance
+                .jobs
+                .iter()
+                .map(|&length| length * noise.sample(&mut rng))
+                .collect();
+            for &lambda in &[0.0, 0.3, 1.0] {
+                let ctx = Context::default();
+                assert_close(
+                    pts(&problem, &pred, lambda, &ctx).weighted_completion_time(&problem.weights),
+                    preferrential_rr(&problem.instance, &pred, lambda, &ctx)
+                        .weighted_completion_time(&problem.weights),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! Algorithms for known or predicted length distributions rather than point predictions: the
+//! Gittins index policy and SEPT/WSEPT.
+
+use std::collections::HashMap;
+
+use crate::{
+    instance::ProblemInstance,
+    prediction::{DistributionPrediction, InstancePrediction, JobDistribution, Prediction},
+    schedule::Schedule,
+    scheduler::{Context, Scheduler},
+    simulator::{simulate, Policy, SimJob},
+};
+
+/// Number of points summarising the workload distribution if no parameter is given.
+const DEFAULT_POINTS: usize = 16;
+
+/// Where the stochastic algorithms take the length distributions of the jobs from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Knowledge {
+    /// The predicted distributions; point predictions count as certain.
+    Prediction,
+    /// The distribution of all lengths of the instance, summarised by `points` points, for
+    /// every job. This is clairvoyance of the distribution but not of the individual lengths.
+    Workload { points: usize },
+}
+
+impl Knowledge {
+    /// The workload distribution summarised by `points` points, or a default number.
+    pub fn workload(points: Option<usize>) -> Self {
+        Knowledge::Workload {
+            points: points.unwrap_or(DEFAULT_POINTS),
+        }
+    }
+}
+
+/// The distributions of the jobs under `knowledge` and the index of every job's distribution.
+fn distributions(
+    problem: &ProblemInstance,
+    pred: &Prediction,
+    knowledge: Knowledge,
+) -> (Vec<JobDistribution>, Vec<usize>) {
+    let n = problem.instance.len();
+    match (knowledge, pred) {
+        (Knowledge::Workload { points }, _) => (
+            vec![JobDistribution::summarise(&problem.instance.jobs, points)],
+            vec![0; n],
+        ),
+        (Knowledge::Prediction, Prediction::Distribution(pred)) => {
+            (pred.jobs.clone(), (0..n).collect())
+        }
+        (Knowledge::Prediction, _) => (
+            DistributionPrediction::point(pred.lengths()).jobs,
+            (0..n).collect(),
+        ),
+    }
+}
+
+/// Runs the `m` jobs of highest weighted Gittins index with respect to their distributions,
+/// which is optimal on a single machine without release dates if the lengths follow these
+/// distributions. Job `j` has distribution `dists[of[j]]`. A job that outlives all samples of
+/// its distribution is predicted to need as much processing again as it has received.
+pub fn gittins(
+    problem: &ProblemInstance,
+    dists: &[JobDistribution],
+    of: &[usize],
+    ctx: &Context,
+) -> Schedule {
+    simulate(
+        problem,
+        &means(dists, of),
+        &mut GittinsPolicy {
+            m: problem.m,
+            dists,
+            of,
+            quanta: vec![],
+        },
+        ctx,
+    )
+}
+
+/// Runs the `m` jobs of largest ratio of weight (1 unless `weighted`) and expected length.
+pub fn sept(
+    problem: &ProblemInstance,
+    dists: &[JobDistribution],
+    of: &[usize],
+    weighted: bool,
+    ctx: &Context,
+) -> Schedule {
+    simulate(
+        problem,
+        &means(dists, of),
+        &mut SeptPolicy {
+            m: problem.m,
+            weighted,
+        },
+        ctx,
+    )
+}
+
+fn means(dists: &[JobDistribution], of: &[usize]) -> InstancePrediction {
+    of.iter().map(|&d| dists[d].mean()).collect()
+}
+
+struct GittinsPolicy<'a> {
+    m: usize,
+    dists: &'a [JobDistribution],
+    of: &'a [usize],
+    /// Quanta of the jobs of the last call to `rates`, after which their indices change.
+    quanta: Vec<f64>,
+}
+
+impl Policy for GittinsPolicy<'_> {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        // jobs with the same distribution and attained processing have the same index, e.g. all
+        // jobs that have not started under the workload distribution
+        let mut cache: HashMap<(usize, u64), (f64, f64)> = HashMap::new();
+        let indices: Vec<(f64, f64)> = jobs
+            .iter()
+            .map(|job| {
+                let d = self.of[job.id];
+                *cache.entry((d, job.attained.to_bits())).or_insert_with(|| {
+                    self.dists[d]
+                        .gittins(job.attained)
+                        .unwrap_or((1.0 / job.attained, job.attained))
+                })
+            })
+            .collect();
+        self.quanta = indices.iter().map(|&(_, quantum)| quantum).collect();
+
+        let key = |idx: usize| jobs[idx].weight * indices[idx].0;
+        priority_rates(jobs, self.m, key)
+    }
+
+    fn horizon(&self, _time: f64, _jobs: &[SimJob], rates: &[f64]) -> Option<f64> {
+        rates
+            .iter()
+            .zip(&self.quanta)
+            .filter(|(&rate, _)| rate > 0.0)
+            .map(|(_, &quantum)| quantum)
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+}
+
+struct SeptPolicy {
+    m: usize,
+    weighted: bool,
+}
+
+impl Policy for SeptPolicy {
+    fn rates(&mut self, _time: f64, jobs: &[SimJob]) -> Vec<f64> {
+        let key = |idx: usize| {
+            let weight = if self.weighted { jobs[idx].weight } else { 1.0 };
+            weight / jobs[idx].pred
+        };
+        priority_rates(jobs, self.m, key)
+    }
+}
+
+/// Rate 1 for the `m` jobs of largest `key`, ties broken by job id.
+fn priority_rates<F: Fn(usize) -> f64>(jobs: &[SimJob], m: usize, key: F) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..jobs.len()).collect();
+    order.sort_by(|&i, &j| {
+        key(j)
+            .partial_cmp(&key(i))
+            .unwrap()
+            .then(jobs[i].id.cmp(&jobs[j].id))
+    });
+    let mut rates = vec![0.0; jobs.len()];
+    for idx in order.into_iter().take(m) {
+        rates[idx] = 1.0;
+    }
+    rates
+}
+
+pub struct Gittins {
+    pub knowledge: Knowledge,
+}
+
+impl Scheduler for Gittins {
+    fn name(&self) -> &str {
+        match self.knowledge {
+            Knowledge::Prediction => "Gittins",
+            Knowledge::Workload { .. } => "Gittins (workload)",
+        }
+    }
+
+    fn param(&self) -> f64 {
+        match self.knowledge {
+            Knowledge::Prediction => 0.0,
+            Knowledge::Workload { points } => points as f64,
+        }
+    }
+
+    fn uses_prediction(&self) -> bool {
+        self.knowledge == Knowledge::Prediction
+    }
+
+    fn accepts_distributions(&self) -> bool {
+        self.knowledge == Knowledge::Prediction
+    }
+
+    /// Point predictions are treated as certain, so that the policy runs in predicted SRPT
+    /// order until a job outlives its prediction.
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        let (dists, of) = distributions(problem, pred, self.knowledge);
+        gittins(problem, &dists, &of, ctx)
+    }
+}
+
+pub struct Sept {
+    pub weighted: bool,
+    pub knowledge: Knowledge,
+}
+
+impl Scheduler for Sept {
+    fn name(&self) -> &str {
+        match (self.weighted, self.knowledge) {
+            (false, Knowledge::Prediction) => "SEPT",
+            (true, Knowledge::Prediction) => "WSEPT",
+            (false, Knowledge::Workload { .. }) => "SEPT (workload)",
+            (true, Knowledge::Workload { .. }) => "WSEPT (workload)",
+        }
+    }
+
+    fn param(&self) -> f64 {
+        0.0
+    }
+
+    fn uses_prediction(&self) -> bool {
+        self.knowledge == Knowledge::Prediction
+    }
+
+    fn accepts_distributions(&self) -> bool {
+        self.knowledge == Knowledge::Prediction
+    }
+
+    /// Point predictions are treated as certain, which makes SEPT predicted SPT and WSEPT
+    /// predicted P-WSPT. Under the workload distribution all jobs have the same expected length.
+    fn schedule(&self, problem: &ProblemInstance, pred: &Prediction, ctx: &Context) -> Schedule {
+        let (dists, of) = distributions(problem, pred, self.knowledge);
+        sept(problem, &dists, &of, self.weighted, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    /// A job of length 10 with distribution {1, 10} and a job of length 3 known to be 3.
+    fn problem(weights: Vec<f64>) -> (ProblemInstance, Vec<JobDistribution>) {
+        let problem = ProblemInstance {
+            weights,
+            ..ProblemInstance::single_machine(vec![10.0, 3.0].into())
+        };
+        let dists = vec![
+            JobDistribution::new(vec![10.0, 1.0]),
+            JobDistribution::point(3.0),
+        ];
+        (problem, dists)
+    }
+
+    #[test]
+    fn gittins_index_of_two_samples() {
+        let dist = JobDistribution::new(vec![10.0, 1.0]);
+        // a quantum of 1 completes half of the jobs at a mean cost of 1
+        let (index, quantum) = dist.gittins(0.0).unwrap();
+        assert_close(index, 0.5);
+        assert_close(quantum, 1.0);
+        // past 1 only the sample 10 is left
+        let (index, quantum) = dist.gittins(1.0).unwrap();
+        assert_close(index, 1.0 / 9.0);
+        assert_close(quantum, 9.0);
+        assert_eq!(dist.gittins(10.0), None);
+    }
+
+    #[test]
+    fn gittins_gives_up_on_a_job_that_outlives_its_quantum() {
+        // the uncertain job runs for its quantum of 1, then the known job overtakes it
+        let (problem, dists) = problem(vec![1.0, 1.0]);
+        let schedule = gittins(&problem, &dists, &[0, 1], &Context::default());
+        assert_close(schedule.completions[0], 13.0);
+        assert_close(schedule.completions[1], 4.0);
+    }
+
+    #[test]
+    fn sept_and_wsept_order_by_expected_length() {
+        // expected lengths 5.5 and 3
+        let (problem, dists) = problem(vec![2.0, 1.0]);
+        let schedule = sept(&problem, &dists, &[0, 1], false, &Context::default());
+        assert_close(schedule.completions[0], 13.0);
+        assert_close(schedule.completions[1], 3.0);
+        // 2 / 5.5 beats 1 / 3
+        let schedule = sept(&problem, &dists, &[0, 1], true, &Context::default());
+        assert_close(schedule.completions[0], 10.0);
+        assert_close(schedule.completions[1], 13.0);
+    }
+}
@@ -0,0 +1,191 @@
+//! Instances from workload traces in the Standard Workload Format of the Parallel Workloads
+//! Archive (<https://www.cs.huji.ac.il/labs/parallel/workload/swf.html>).
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{instance::ProblemInstance, prediction::InstancePrediction};
+
+/// The fields of an SWF line that are used here, in seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceJob {
+    pub submit: f64,
+    pub run_time: f64,
+    pub requested_time: Option<f64>,
+}
+
+/// Reads the jobs of an SWF file. Header comments (`;`) are skipped, as are jobs without a
+/// positive run time (cancelled before they started, or not recorded).
+pub fn read_swf(path: &Path) -> Result<Vec<TraceJob>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("cannot read trace {}", path.display()))?;
+    let mut jobs = vec![];
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        let fields: Vec<f64> = line
+            .split_whitespace()
+            .map(|f| f.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| anyhow!("{}:{}: invalid SWF line", path.display(), number + 1))?;
+        if fields.len() < 9 {
+            bail!(
+                "{}:{}: expected at least the first 9 of the 18 SWF fields, got {}",
+                path.display(),
+                number + 1,
+                fields.len()
+            );
+        }
+        // 1-based SWF fields: 2 submit time, 4 run time, 9 requested time; -1 marks missing
+        let (submit, run_time, requested_time) = (fields[1], fields[3], fields[8]);
+        if run_time <= 0.0 || submit < 0.0 {
+            continue;
+        }
+        jobs.push(TraceJob {
+            submit,
+            run_time,
+            requested_time: if requested_time > 0.0 {
+                Some(requested_time)
+            } else {
+                None
+            },
+        });
+    }
+    Ok(jobs)
+}
+
+/// `count` instances of `length` consecutive jobs on `m` machines with unit weights. Release
+/// dates are the submit times relative to the first job of the instance. With `requested`,
+/// only jobs with a requested time are used and it becomes their prediction. Fails if the
+/// trace has too few such jobs.
+pub fn instances(
+    jobs: &[TraceJob],
+    length: usize,
+    count: usize,
+    m: usize,
+    requested: bool,
+) -> Result<Vec<(ProblemInstance, Option<InstancePrediction>)>> {
+    if length == 0 {
+        bail!("instances must have at least one job");
+    }
+    let jobs: Vec<&TraceJob> = jobs
+        .iter()
+        .filter(|job| !requested || job.requested_time.is_some())
+        .collect();
+    if jobs.len() < length * count {
+        bail!(
+            "{} usable jobs are too few for {} instances of {} jobs, at most {} fit",
+            jobs.len(),
+            count,
+            length,
+            jobs.len() / length
+        );
+    }
+    Ok(jobs
+        .chunks_exact(length)
+        .take(count)
+        .map(|window| {
+            let start = window
+                .iter()
+                .map(|job| job.submit)
+                .fold(f64::INFINITY, f64::min);
+            let problem = ProblemInstance {
+                instance: window
+                    .iter()
+                    .map(|job| job.run_time)
+                    .collect::<Vec<f64>>()
+                    .into(),
+                weights: vec![1.0; length],
+                releases: window.iter().map(|job| job.submit - start).collect(),
+                m,
+                speeds: vec![],
+                machine_lengths: vec![],
+                speedups: vec![],
+            };
+            let pred = if requested {
+                Some(
+                    window
+                        .iter()
+                        .map(|job| job.requested_time.unwrap())
+                        .collect::<Vec<f64>>()
+                        .into(),
+                )
+            } else {
+                None
+            };
+            (problem, pred)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SWF: &str = "\
+; Version: 2.2
+; MaxNodes: 4
+
+1 100 5 30 1 -1 -1 1 60 -1 1 1 1 -1 1 -1 -1 -1
+2 110 0 -1 1 -1 -1 1 60 -1 0 1 1 -1 1 -1 -1 -1
+3 130 2 10 2 -1 -1 2 -1 -1 1 1 1 -1 1 -1 -1 -1
+4 150 0 20 1 -1 -1 1 25 -1 1 1 1 -1 1 -1 -1 -1
+";
+
+    fn read(name: &str, content: &str) -> Result<Vec<TraceJob>> {
+        let path = std::env::temp_dir().join(format!("schedules-{}-{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        let jobs = read_swf(&path);
+        fs::remove_file(&path).unwrap();
+        jobs
+    }
+
+    #[test]
+    fn reads_swf() {
+        // the second job never ran
+        let jobs = read("read.swf", SWF).unwrap();
+        assert_eq!(
+            jobs,
+            vec![
+                TraceJob {
+                    submit: 100.0,
+                    run_time: 30.0,
+                    requested_time: Some(60.0),
+                },
+                TraceJob {
+                    submit: 130.0,
+                    run_time: 10.0,
+                    requested_time: None,
+                },
+                TraceJob {
+                    submit: 150.0,
+                    run_time: 20.0,
+                    requested_time: Some(25.0),
+                },
+            ]
+        );
+
+        assert!(read("short.swf", "1 100 5 30 1\n").is_err());
+        assert!(read("invalid.swf", "1 100 5 thirty 1 -1 -1 1 60\n").is_err());
+    }
+
+    #[test]
+    fn cuts_instances() {
+        let jobs = read("cut.swf", SWF).unwrap();
+        let cut = instances(&jobs, 1, 3, 2, false).unwrap();
+        assert_eq!(cut.len(), 3);
+        assert_eq!(cut[1].0.instance.jobs, vec![10.0]);
+        assert_eq!(cut[1].0.releases, vec![0.0]);
+        assert!(cut[1].1.is_none());
+
+        // the job without a requested time is skipped
+        let cut = instances(&jobs, 2, 1, 2, true).unwrap();
+        assert_eq!(cut[0].0.instance.jobs, vec![30.0, 20.0]);
+        assert_eq!(cut[0].0.releases, vec![0.0, 50.0]);
+        assert_eq!(cut[0].1.as_ref().unwrap().jobs, vec![60.0, 25.0]);
+        assert!(instances(&jobs, 2, 2, 2, true).is_err());
+    }
+}
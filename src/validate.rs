@@ -0,0 +1,286 @@
+use std::fmt;
+
+use crate::{instance::ProblemInstance, schedule::Schedule};
+
+/// Relative tolerance for floating point comparisons.
+const TOL: f64 = 1e-6;
+
+/// A way in which a schedule is infeasible for its problem.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// Segments of the profile overlap or are not in chronological order.
+    Overlap {
+        time: f64,
+    },
+    /// The `jobs` fastest processed jobs get more than the `jobs` fastest machines' worth of
+    /// work at once (for identical machines, `jobs = m` means more than `m` machines), or
+    /// malleable jobs need `load` machines in total for their rates.
+    Overload {
+        time: f64,
+        jobs: usize,
+        load: f64,
+    },
+    /// A job is processed faster than the fastest machine (or all machines, if malleable)
+    /// allows.
+    RateAboveSpeed {
+        job: usize,
+        time: f64,
+        rate: f64,
+    },
+    BeforeRelease {
+        job: usize,
+        time: f64,
+    },
+    AfterCompletion {
+        job: usize,
+        time: f64,
+    },
+    /// A job received a different amount of processing than its length.
+    WrongAmount {
+        job: usize,
+        time: f64,
+        processed: f64,
+        length: f64,
+    },
+    NotCompleted {
+        job: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Overlap { time } => write!(f, "overlapping segments at time {}", time),
+            Violation::Overload { time, jobs, load } => write!(
+                f,
+                "load {} of {} jobs exceeds their machines at time {}",
+                load, jobs, time
+            ),
+            Violation::RateAboveSpeed { job, time, rate } => {
+                write!(f, "job {} processed at rate {} at time {}", job, rate, time)
+            }
+            Violation::BeforeRelease { job, time } => {
+                write!(
+                    f,
+                    "job {} processed before its release at time {}",
+                    job, time
+                )
+            }
+            Violation::AfterCompletion { job, time } => {
+                write!(
+                    f,
+                    "job {} processed after its completion at time {}",
+                    job, time
+                )
+            }
+            Violation::WrongAmount {
+                job,
+                time,
+                processed,
+                length,
+            } => write!(
+                f,
+                "job {} completed at time {} after {} units of processing instead of {}",
+                job, time, processed, length
+            ),
+            Violation::NotCompleted { job } => write!(f, "job {} never completes", job),
+        }
+    }
+}
+
+/// Checks the recorded profile of `schedule` against `problem` and returns all violations. The
+/// schedule must have been computed with `Context::record_profile` set.
+pub fn validate(problem: &ProblemInstance, schedule: &Schedule) -> Vec<Violation> {
+    let n = problem.instance.len();
+    let mut violations = vec![];
+    let mut processed = vec![0.0; n];
+    let mut last_end: f64 = 0.0;
+    let speeds = problem.machine_speeds();
+
+    for segment in &schedule.profile {
+        if segment.start < last_end - TOL * last_end.max(1.0) || segment.end < segment.start {
+            violations.push(Violation::Overlap {
+                time: segment.start,
+            });
+        }
+        last_end = segment.end;
+
+        if problem.is_malleable() {
+            // every job needs the machines its speed-up curve requires for its rate
+            let load: f64 = segment
+                .rates
+                .iter()
+                .map(|&(job, rate)| problem.speedups[job].machines(rate))
+                .sum();
+            if load > problem.m as f64 * (1.0 + TOL) {
+                violations.push(Violation::Overload {
+                    time: segment.start,
+                    jobs: segment.rates.len(),
+                    load,
+                });
+            }
+        } else {
+            // a profile is feasible iff, for every k, the k largest rates sum to at most the
+            // speeds of the k fastest machines
+            let mut rates: Vec<f64> = segment.rates.iter().map(|&(_, rate)| rate).collect();
+            rates.sort_by(|a, b| b.partial_cmp(a).unwrap());
+            let (mut load, mut capacity) = (0.0, 0.0);
+            for (k, &rate) in rates.iter().enumerate() {
+                load += rate;
+                capacity += speeds.get(k).copied().unwrap_or(0.0);
+                if k > 0 && load > capacity * (1.0 + TOL) {
+                    violations.push(Violation::Overload {
+                        time: segment.start,
+                        jobs: k + 1,
+                        load,
+                    });
+                    break;
+                }
+            }
+        }
+
+        for &(job, rate) in &segment.rates {
+            if rate > problem.max_rate(job) * (1.0 + TOL) {
+                violations.push(Violation::RateAboveSpeed {
+                    job,
+                    time: segment.start,
+                    rate,
+                });
+            }
+            let release = problem.releases[job];
+            if segment.start < release - TOL * release.max(1.0) {
+                violations.push(Violation::BeforeRelease {
+                    job,
+                    time: segment.start,
+                });
+            }
+            let completion = schedule.completions[job];
+            if segment.end > completion + TOL * completion.max(1.0) {
+                violations.push(Violation::AfterCompletion {
+                    job,
+                    time: segment.end,
+                });
+            }
+            processed[job] += rate * (segment.end - segment.start);
+        }
+    }
+
+    for (job, &processed) in processed.iter().enumerate() {
+        let length = problem.instance[job];
+        let processed = processed - schedule.lost[job];
+        if schedule.completions[job].is_nan() {
+            violations.push(Violation::NotCompleted { job });
+        } else if (processed - length).abs() > TOL * length.max(1.0) {
+            violations.push(Violation::WrongAmount {
+                job,
+                time: schedule.completions[job],
+                processed,
+                length,
+            });
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Jobs of lengths 2 and 1 on one machine, the second released at 1.
+    fn problem() -> ProblemInstance {
+        ProblemInstance {
+            releases: vec![0.0, 1.0],
+            ..ProblemInstance::single_machine(vec![2.0, 1.0].into())
+        }
+    }
+
+    /// Start, end and rates of a segment.
+    type Piece<'a> = (f64, f64, &'a [(usize, f64)]);
+
+    fn schedule(segments: &[Piece], completions: &[f64]) -> Schedule {
+        let mut schedule = Schedule::new(completions.len(), true);
+        for &(start, end, rates) in segments {
+            schedule.process(start, end, rates.iter().cloned());
+        }
+        for (job, &time) in completions.iter().enumerate() {
+            if !time.is_nan() {
+                schedule.complete(job, time);
+            }
+        }
+        schedule
+    }
+
+    #[test]
+    fn accepts_feasible_schedules() {
+        let s = schedule(
+            &[(0.0, 1.0, &[(0, 1.0)]), (1.0, 3.0, &[(0, 0.5), (1, 0.5)])],
+            &[3.0, 3.0],
+        );
+        assert_eq!(validate(&problem(), &s), vec![]);
+    }
+
+    #[test]
+    fn rejects_overload_and_rates_above_speed() {
+        let s = schedule(&[(1.0, 2.0, &[(0, 1.0), (1, 1.0)])], &[2.0, 2.0]);
+        assert!(validate(&problem(), &s).contains(&Violation::Overload {
+            time: 1.0,
+            jobs: 2,
+            load: 2.0,
+        }));
+
+        let s = schedule(
+            &[(0.0, 1.0, &[(0, 2.0)]), (1.0, 2.0, &[(1, 1.0)])],
+            &[1.0, 2.0],
+        );
+        assert_eq!(
+            validate(&problem(), &s),
+            vec![Violation::RateAboveSpeed {
+                job: 0,
+                time: 0.0,
+                rate: 2.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_processing_outside_release_and_completion() {
+        let s = schedule(
+            &[(0.0, 1.0, &[(1, 1.0)]), (1.0, 3.0, &[(0, 1.0)])],
+            &[2.0, 1.0],
+        );
+        assert_eq!(
+            validate(&problem(), &s),
+            vec![
+                Violation::BeforeRelease { job: 1, time: 0.0 },
+                Violation::AfterCompletion { job: 0, time: 3.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_amounts_and_missing_completions() {
+        let s = schedule(&[(0.0, 1.0, &[(0, 1.0)])], &[1.0, f64::NAN]);
+        assert_eq!(
+            validate(&problem(), &s),
+            vec![
+                Violation::WrongAmount {
+                    job: 0,
+                    time: 1.0,
+                    processed: 1.0,
+                    length: 2.0,
+                },
+                Violation::NotCompleted { job: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_overlapping_segments() {
+        let s = schedule(
+            &[(0.0, 2.0, &[(0, 1.0)]), (1.0, 2.0, &[(1, 1.0)])],
+            &[2.0, 2.0],
+        );
+        assert!(validate(&problem(), &s).contains(&Violation::Overlap { time: 1.0 }));
+    }
+}